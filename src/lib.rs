@@ -17,7 +17,7 @@
 //!         println!("Partition #{}: type = {:?}, size = {} bytes, starting lba = {}",
 //!             i,
 //!             p.partition_type_guid,
-//!             p.size().unwrap() * gpt.sector_size,
+//!             p.size_bytes(gpt.sector_size()).unwrap(),
 //!             p.starting_lba);
 //!     }
 //! }
@@ -86,11 +86,25 @@ use thiserror::Error;
 /// Linux specific helpers
 #[cfg(all(target_os = "linux", feature = "nix"))]
 pub mod linux;
+/// Well-known partition types
+pub mod partition_types;
+/// Helpers to build in-memory disks for tests
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 const DEFAULT_ALIGN: u64 = 2048;
 const MAX_ALIGN: u64 = 16384;
+const MAX_PARTITION_ENTRIES: u32 = 1 << 20;
+// NOTE: position of every byte of a GUID in its textual form, in the order they are stored on disk
+//       (the first 3 groups are stored in little-endian)
+pub(crate) const GUID_TEXT_POSITIONS: [usize; 16] =
+    [6, 4, 2, 0, 11, 9, 16, 14, 19, 21, 24, 26, 28, 30, 32, 34];
+// NOTE: the size of an array of MAX_PARTITION_ENTRIES entries of 128 bytes
+const MAX_PARTITION_ENTRY_ARRAY_SIZE: u64 = (MAX_PARTITION_ENTRIES as u64) * 128;
 
 /// An error that can be produced while reading, writing or managing a GPT.
+///
+/// New variants may be added in minor releases: a `match` on this type must have a wildcard arm.
 #[derive(Debug, Error)]
 #[non_exhaustive]
 pub enum Error {
@@ -111,6 +125,11 @@ pub enum Error {
     /// An error that occurs when the header's size (in bytes) isn't what would be expected (92).
     #[error("invalid header size")]
     InvalidHeaderSize,
+    /// An error that occurs when the size (in bytes) of the partition entries declared in the
+    /// header isn't 128 multiplied by a power of two, or when the partition entry array would be
+    /// bigger than 128 MiB.
+    #[error("invalid size of partition entry: {0}")]
+    InvalidPartitionEntrySize(u32),
     /// An error that occurs when the CRC32 checksum of the header doesn't match the expected
     /// checksum for the actual header.
     #[error("corrupted CRC32 checksum ({0} != {1})")]
@@ -119,15 +138,34 @@ pub enum Error {
     /// the expected checksum for the actual partition entries array.
     #[error("corrupted partition entry array CRC32 checksum ({0} != {1})")]
     InvalidPartitionEntryArrayChecksum(u32, u32),
+    /// An error that occurs when the number of partition entries is implausible (`0` or greater
+    /// than 2^20).
+    #[error("invalid number of partition entries: {0}")]
+    InvalidNumberOfPartitionEntries(u32),
     /// An error that occurs when reading a GPT from a file did not succeeded.
     ///
     /// The first argument is the error that occurred when trying to read the primary header.
     /// The second argument is the error that occurred when trying to read the backup header.
+    ///
+    /// The error of the primary header is used as the `source()` of this error.
     #[error("could not read primary header ({0}) nor backup header ({1})")]
-    ReadError(Box<Error>, Box<Error>),
+    ReadError(#[source] Box<Error>, Box<Error>),
+    /// An error that occurs when no GPT header could be found and the first sector doesn't
+    /// contain a protective MBR either.
+    ///
+    /// This usually happens when opening a partition (`/dev/sda1`) instead of the whole disk
+    /// (`/dev/sda`), or a disk that has no partition table at all.
+    #[error(
+        "no partition table found: this might be a partition rather than a whole disk (or the \
+        disk is not partitioned)"
+    )]
+    NoPartitionTable,
     /// An error that occurs when there is not enough space left on the table to continue.
     #[error("no space left")]
     NoSpaceLeft,
+    /// An error that occurs when all the partition entries of the array are already used.
+    #[error("no free partition entry left")]
+    NoFreeSlot,
     /// An error that occurs when there are partitions with the same GUID in the same array.
     #[error("conflict of partition GUIDs")]
     ConflictPartitionGUID,
@@ -141,12 +179,20 @@ pub enum Error {
         and must fit within the disk"
     )]
     InvalidPartitionBoundaries,
+    /// An error that occurs when a used partition starts before the first usable LBA (typically
+    /// when a partition table is copied from a disk with a smaller partition entry array).
+    #[error("partition {0} starts before the first usable LBA")]
+    PartitionBeforeFirstUsableLba(u32),
     /// An error that occurs when the user provide an invalid partition number.
     ///
     /// The partition number must be between 1 and `number_of_partition_entries` (usually 128)
     /// included.
     #[error("invalid partition number: {0}")]
     InvalidPartitionNumber(u32),
+    /// An error that occurs when reducing the number of partition entries would drop a used
+    /// partition entry.
+    #[error("partition entry {0} is used and can not be dropped")]
+    UsedPartitionEntryDropped(u32),
     /// An error that occurs when the user attempts to access information for an unused partition.
     #[error("unused partition")]
     UnusedPartition,
@@ -156,14 +202,119 @@ pub enum Error {
     /// An arithmetic operation overflowed.
     #[error("an arithmetic operation overflowed")]
     Overflow,
+    /// An error that occurs when the name given doesn't match any known partition type.
+    #[error("unknown partition type: {0}")]
+    UnknownPartitionType(String),
+    /// An error that occurs when the name given matches more than one known partition type.
+    #[error("ambiguous partition type: {0}")]
+    AmbiguousPartitionType(String),
+    /// An error that occurs when a partition name can not be stored in a partition entry.
+    #[error("invalid partition name: {0}")]
+    InvalidPartitionName(String),
+    /// An error that occurs when the sector size given isn't a power of two greater or equal to
+    /// 512.
+    #[error("invalid sector size: {0}")]
+    InvalidSectorSize(u64),
+    /// An error that occurs when the alignment given is 0 (or is not a multiple of the sector size
+    /// when given in bytes).
+    #[error("invalid alignment: {0}")]
+    InvalidAlignment(u64),
+    /// An error that occurs when a GUID in its textual form can't be parsed.
+    #[error("invalid GUID: {0}")]
+    InvalidGuid(String),
+    /// An error that occurs when a partition entry specification can't be parsed.
+    #[error("invalid partition entry specification: {0}")]
+    InvalidEntrySpec(String),
+    /// An error that occurs when the disk GUID of the GPT read isn't the one expected.
+    #[error("disk GUID mismatch (expected {expected:?}, found {found:?})")]
+    DiskGuidMismatch {
+        /// The disk GUID that was expected.
+        expected: [u8; 16],
+        /// The disk GUID that was found on the disk.
+        found: [u8; 16],
+    },
+    /// An error that occurs when the disk is too small for the GPT (e.g. the backup header would
+    /// be located after the end of the disk).
+    #[error("the disk is too small ({required} bytes required, found {found} bytes)")]
+    DiskTooSmall {
+        /// The minimum size of the disk (in bytes) to write the GPT.
+        required: u64,
+        /// The actual size of the disk (in bytes).
+        found: u64,
+    },
+    /// An error that occurs when the size of the disk (in sectors) isn't the one described by the
+    /// GPT. This usually happens when the disk has been resized (the backup header is not located
+    /// at the end of the disk anymore) or when the wrong sector size is used.
+    #[error("disk size mismatch (expected {expected} sectors, found {found} sectors)")]
+    DiskSizeMismatch {
+        /// The size of the disk (in sectors) according to the GPT.
+        expected: u64,
+        /// The actual size of the disk (in sectors).
+        found: u64,
+    },
 }
 
 /// The result of reading, writing or managing a GPT.
 pub type Result<T> = std::result::Result<T, Error>;
 
+fn check_sector_size(sector_size: u64) -> Result<()> {
+    if sector_size < 512 || !sector_size.is_power_of_two() {
+        return Err(Error::InvalidSectorSize(sector_size));
+    }
+
+    Ok(())
+}
+
+fn partition_array_sectors(
+    number_of_partition_entries: u32,
+    size_of_partition_entry: u32,
+    sector_size: u64,
+) -> u64 {
+    (u64::from(number_of_partition_entries) * u64::from(size_of_partition_entry)).saturating_sub(1)
+        / sector_size
+        + 1
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Parse a number of sectors, or a size in bytes if it is followed by a unit (`B`, `KiB`, `MiB`,
+/// `GiB` or `TiB`, the `iB` can be omitted). Sizes in bytes are rounded up to whole sectors.
+fn parse_sectors(value: &str, sector_size: u64) -> Option<u64> {
+    let i = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let number = value[..i].parse::<u64>().ok()?;
+    let unit: u64 = match &value[i..] {
+        "" => return Some(number),
+        "B" => 1,
+        "K" | "KiB" => 1 << 10,
+        "M" | "MiB" => 1 << 20,
+        "G" | "GiB" => 1 << 30,
+        "T" | "TiB" => 1 << 40,
+        _ => return None,
+    };
+    let bytes = number.checked_mul(unit)?;
+
+    Some(bytes / sector_size + u64::from(bytes % sector_size != 0))
+}
+
+fn cmp_starting_lba(a: &GPTPartitionEntry, b: &GPTPartitionEntry) -> Ordering {
+    match (a.is_used(), b.is_used()) {
+        (true, true) => a.starting_lba.cmp(&b.starting_lba),
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => Ordering::Equal,
+    }
+}
+
 /// A GUID Partition Table header as describe on
 /// [Wikipedia's page](https://en.wikipedia.org/wiki/GUID_Partition_Table#Partition_table_header_(LBA_1)).
-#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, Hash)]
 pub struct GPTHeader {
     /// GPT signature (must be "EFI PART").
     pub signature: [u8; 8],
@@ -184,10 +335,14 @@ pub struct GPTHeader {
     /// Location (in sectors) of the last usable sector.
     pub last_usable_lba: u64,
     /// 16 bytes representing the UUID of the GPT.
+    ///
+    /// This is the only identifier of the disk: unlike some other partition tables, the GPT has no
+    /// disk label. See [`GuidDisplay`] and [`parse_guid`] to convert it from and to its textual
+    /// form.
     pub disk_guid: [u8; 16],
     /// Location (in sectors) of the partition entries array.
     ///
-    /// This is always `2` if the header is a primary header and not a backup header.
+    /// This is usually `2` if the header is a primary header and not a backup header.
     pub partition_entry_lba: u64,
     /// Number of partition entries in the array.
     pub number_of_partition_entries: u32,
@@ -203,6 +358,14 @@ impl GPTHeader {
     where
         R: Read + Seek,
     {
+        check_sector_size(sector_size)?;
+        let len = reader.seek(SeekFrom::End(0))? / sector_size;
+
+        Self::new_with_len(len, sector_size, disk_guid)
+    }
+
+    /// Make a new GPT header for a disk of `len` sectors.
+    fn new_with_len(len: u64, sector_size: u64, disk_guid: [u8; 16]) -> Result<GPTHeader> {
         let mut gpt = GPTHeader {
             signature: [0x45, 0x46, 0x49, 0x20, 0x50, 0x41, 0x52, 0x54],
             revision: [0x00, 0x00, 0x01, 0x00],
@@ -219,7 +382,7 @@ impl GPTHeader {
             size_of_partition_entry: 128,
             partition_entry_array_crc32: 0,
         };
-        gpt.update_from(reader, sector_size)?;
+        gpt.update_from_len(len, sector_size)?;
 
         Ok(gpt)
     }
@@ -230,9 +393,16 @@ impl GPTHeader {
     ///
     /// The field `last_usable_lba` is not updated to reflect the actual size of the disk. You must
     /// do this yourself by calling `update_from`.
-    pub fn read_from<R: ?Sized>(reader: &mut R) -> Result<GPTHeader>
+    pub fn read_from<R>(reader: &mut R) -> Result<GPTHeader>
     where
-        R: Read + Seek,
+        R: Read + Seek + ?Sized,
+    {
+        Self::read_from_impl(reader, false, true)
+    }
+
+    fn read_from_impl<R>(reader: &mut R, any_revision: bool, verify_crc: bool) -> Result<GPTHeader>
+    where
+        R: Read + Seek + ?Sized,
     {
         let gpt: GPTHeader = deserialize_from(reader)?;
 
@@ -240,7 +410,7 @@ impl GPTHeader {
             return Err(Error::InvalidSignature);
         }
 
-        if gpt.revision != [0x00, 0x00, 0x01, 0x00] {
+        if !any_revision && !gpt.has_standard_revision() {
             return Err(Error::InvalidRevision);
         }
 
@@ -248,30 +418,103 @@ impl GPTHeader {
             return Err(Error::InvalidHeaderSize);
         }
 
+        if gpt.size_of_partition_entry < 128 || !gpt.size_of_partition_entry.is_power_of_two() {
+            return Err(Error::InvalidPartitionEntrySize(
+                gpt.size_of_partition_entry,
+            ));
+        }
+
         let sum = gpt.generate_crc32_checksum();
-        if gpt.crc32_checksum != sum {
+        if verify_crc && gpt.crc32_checksum != sum {
             return Err(Error::InvalidChecksum(gpt.crc32_checksum, sum));
         }
 
+        if gpt.number_of_partition_entries > MAX_PARTITION_ENTRIES {
+            return Err(Error::InvalidNumberOfPartitionEntries(
+                gpt.number_of_partition_entries,
+            ));
+        }
+
+        // NOTE: the whole array is read and hashed, a huge size would hang on a crafted header
+        if u64::from(gpt.number_of_partition_entries) * u64::from(gpt.size_of_partition_entry)
+            > MAX_PARTITION_ENTRY_ARRAY_SIZE
+        {
+            return Err(Error::InvalidPartitionEntrySize(
+                gpt.size_of_partition_entry,
+            ));
+        }
+
         Ok(gpt)
     }
 
+    /// Get an iterator that reads lazily the partition entries of the array of this header. Every
+    /// partition entry is read only when the iterator reaches it, which allows early-exit
+    /// searches without reading the whole array.
+    ///
+    /// Note that the CRC32 checksum of the partition entry array is not verified.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// use std::io::{Seek, SeekFrom};
+    ///
+    /// let ss = 512;
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// f.seek(SeekFrom::Start(ss)).expect("could not seek");
+    /// let header = gptman::GPTHeader::read_from(&mut f)
+    ///     .expect("could not read the header");
+    ///
+    /// let first_unused = header
+    ///     .entries_iter(&mut f, ss)
+    ///     .position(|x| x.map(|x| x.is_unused()).unwrap_or(false));
+    ///
+    /// assert_eq!(first_unused, Some(2));
+    /// ```
+    pub fn entries_iter<'r, R>(
+        &self,
+        reader: &'r mut R,
+        sector_size: u64,
+    ) -> impl Iterator<Item = Result<GPTPartitionEntry>> + 'r
+    where
+        R: Read + Seek + ?Sized,
+    {
+        let partition_entry_lba = self.partition_entry_lba;
+        let size_of_partition_entry = u64::from(self.size_of_partition_entry);
+
+        (0..self.number_of_partition_entries).map(move |i| {
+            let position = partition_entry_lba
+                .checked_mul(sector_size)
+                .and_then(|x| x.checked_add(u64::from(i) * size_of_partition_entry))
+                .ok_or(Error::Overflow)?;
+            reader.seek(SeekFrom::Start(position))?;
+
+            Ok(GPTPartitionEntry::read_from(reader)?)
+        })
+    }
+
     /// Write the GPT header into a writer. This operation will update the CRC32 checksums of the
     /// current struct and seek at the location `primary_lba` before trying to write to disk.
-    pub fn write_into<W: ?Sized>(
+    ///
+    /// The whole sector of the header is wrote: the bytes after the end of the header (and before
+    /// the end of the sector) are filled with zeroes, whatever was there before.
+    pub fn write_into<W>(
         &mut self,
         mut writer: &mut W,
         sector_size: u64,
         partitions: &[GPTPartitionEntry],
     ) -> Result<()>
     where
-        W: Write + Seek,
+        W: Write + Seek + ?Sized,
     {
         self.update_partition_entry_array_crc32(partitions);
         self.update_crc32_checksum();
 
         writer.seek(SeekFrom::Start(self.primary_lba * sector_size))?;
         serialize_into(&mut writer, &self)?;
+        let padding = sector_size.saturating_sub(u64::from(self.header_size));
+        io::copy(&mut io::repeat(0).take(padding), &mut writer)?;
 
         for i in 0..self.number_of_partition_entries {
             writer.seek(SeekFrom::Start(
@@ -279,6 +522,8 @@ impl GPTHeader {
                     + u64::from(i) * u64::from(self.size_of_partition_entry),
             ))?;
             serialize_into(&mut writer, &partitions[i as usize])?;
+            let padding = u64::from(self.size_of_partition_entry).saturating_sub(128);
+            io::copy(&mut io::repeat(0).take(padding), &mut writer)?;
         }
 
         Ok(())
@@ -300,21 +545,24 @@ impl GPTHeader {
     }
 
     /// Generate the CRC32 checksum of the partition entry array.
+    ///
+    /// If the partition entries are larger than 128 bytes (see `size_of_partition_entry`), every
+    /// entry is padded with zeroes (like they are when they are wrote).
     pub fn generate_partition_entry_array_crc32(&self, partitions: &[GPTPartitionEntry]) -> u32 {
-        let mut clone = self.clone();
-        clone.partition_entry_array_crc32 = 0;
+        const ZEROES: [u8; 128] = [0; 128];
+
         let crc = Crc::<u32>::new(&CRC_32_ISO_HDLC);
         let mut digest = crc.digest();
-        let mut wrote = 0;
         for x in partitions {
             let data = serialize(&x).expect("could not serialize");
             digest.update(&data);
-            wrote += data.len();
+            let mut padding = (self.size_of_partition_entry as usize).saturating_sub(data.len());
+            while padding > 0 {
+                let len = padding.min(ZEROES.len());
+                digest.update(&ZEROES[..len]);
+                padding -= len;
+            }
         }
-        assert_eq!(
-            wrote as u32,
-            clone.size_of_partition_entry * clone.number_of_partition_entries
-        );
 
         digest.finalize()
     }
@@ -327,38 +575,69 @@ impl GPTHeader {
     /// Updates the header to match the specifications of the seeker given in argument.
     /// `first_usable_lba`, `last_usable_lba`, `primary_lba`, `backup_lba`, `partition_entry_lba`
     /// will be updated after this operation.
-    pub fn update_from<S: ?Sized>(&mut self, seeker: &mut S, sector_size: u64) -> Result<()>
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the disk is too small to hold the headers and the
+    /// partition entry arrays with at least one usable sector (nothing is changed in that case).
+    pub fn update_from<S>(&mut self, seeker: &mut S, sector_size: u64) -> Result<()>
     where
-        S: Seek,
+        S: Seek + ?Sized,
     {
-        let partition_array_size = (u64::from(self.number_of_partition_entries)
-            * u64::from(self.size_of_partition_entry)
-            - 1)
-            / sector_size
-            + 1;
         let len = seeker.seek(SeekFrom::End(0))? / sector_size;
-        if self.primary_lba == 1 {
+
+        self.update_from_len(len, sector_size)
+    }
+
+    /// Updates the header like `update_from` for a disk of `len` sectors.
+    fn update_from_len(&mut self, len: u64, sector_size: u64) -> Result<()> {
+        let partition_array_size = partition_array_sectors(
+            self.number_of_partition_entries,
+            self.size_of_partition_entry,
+            sector_size,
+        );
+        let last_usable_lba = len
+            .checked_sub(partition_array_size + 1 + 1)
+            .ok_or(Error::InvalidPartitionBoundaries)?;
+        // NOTE: the partition entry array of the primary header follows the primary header (usually
+        //       at LBA 2) while the partition entry array of the backup header precedes the backup
+        //       header (which is located at the end of the disk)
+        let first_usable_lba = if self.is_primary() {
+            self.partition_entry_lba.checked_add(partition_array_size)
+        } else {
+            self.backup_lba
+                .checked_add(1)
+                .and_then(|x| x.checked_add(partition_array_size))
+        }
+        .ok_or(Error::Overflow)?;
+        if last_usable_lba < first_usable_lba {
+            return Err(Error::InvalidPartitionBoundaries);
+        }
+
+        self.last_usable_lba = last_usable_lba;
+        self.first_usable_lba = first_usable_lba;
+        if self.is_primary() {
             self.backup_lba = len - 1;
         } else {
             self.primary_lba = len - 1;
-        }
-        self.last_usable_lba = len - partition_array_size - 1 - 1;
-        self.first_usable_lba = 2 + partition_array_size;
-        // NOTE: the partition_entry_lba is either 2 either something near the end of the disk.
-        //       If it is something near the end of the disk, it means the self object is a backup
-        //       GPT header (which is located at the end of the disk) and its partition_entry_lba
-        //       must be updated accordingly
-        if self.partition_entry_lba != 2 {
-            self.partition_entry_lba = self.last_usable_lba + 1;
+            self.partition_entry_lba = last_usable_lba + 1;
         }
 
         Ok(())
     }
 
-    /// Returns `true` if the `GPTHeader` is a primary copy (the header is located at the beginning
-    /// of the disk).
+    /// Returns `true` if the revision of the header is the only standard revision (00 00 01 00).
+    pub fn has_standard_revision(&self) -> bool {
+        self.revision == [0x00, 0x00, 0x01, 0x00]
+    }
+
+    /// Returns `true` if the `GPTHeader` is a primary copy (the header is located before its backup
+    /// copy, usually at the beginning of the disk).
+    ///
+    /// A header which doesn't know yet the location of its backup copy (`backup_lba` is `0`) is
+    /// considered as a primary copy.
     pub fn is_primary(&self) -> bool {
-        self.primary_lba == 1
+        self.backup_lba == 0 || self.primary_lba < self.backup_lba
     }
 
     /// Returns `true` if the `GPTHeader` is a backup copy (the header is located at the end of the
@@ -372,14 +651,229 @@ impl GPTHeader {
 }
 
 /// A wrapper type for `String` that represents a partition's name.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PartitionName(String);
 
 impl PartitionName {
+    /// The maximum length of a name, in UTF-16 code units.
+    pub const MAX_UTF16_LEN: usize = 36;
+
+    /// Returns the number of UTF-16 code units needed to store `name` in a partition entry. This
+    /// can be greater than the number of characters (characters outside of the Basic Multilingual
+    /// Plane need 2 code units).
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// use gptman::PartitionName;
+    ///
+    /// assert_eq!(PartitionName::utf16_len("EFI"), 3);
+    /// assert_eq!(PartitionName::utf16_len("été"), 3);
+    /// assert_eq!(PartitionName::utf16_len("🦀"), 2);
+    ///
+    /// let name = "🦀".repeat(17);
+    /// let counter = format!("{}/{}", PartitionName::utf16_len(&name), PartitionName::MAX_UTF16_LEN);
+    /// assert_eq!(counter, "34/36");
+    /// ```
+    pub fn utf16_len(name: &str) -> usize {
+        name.encode_utf16().count()
+    }
+
+    /// Returns `true` if `name` fits in a partition entry without being truncated (it is not
+    /// longer than [`Self::MAX_UTF16_LEN`] UTF-16 code units).
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// use gptman::PartitionName;
+    ///
+    /// assert!(PartitionName::fits(&"x".repeat(36)));
+    /// assert!(!PartitionName::fits(&"🦀".repeat(19)));
+    /// ```
+    pub fn fits(name: &str) -> bool {
+        Self::utf16_len(name) <= Self::MAX_UTF16_LEN
+    }
+
+    /// Makes a `PartitionName` after checking that it can be stored in a partition entry.
+    ///
+    /// Note that converting a `&str` with `into()` never fails but the name will be truncated
+    /// when written to disk.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the name is longer than 36 UTF-16 code units or if
+    /// it contains a null character (which would end the name on disk).
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// assert!(gptman::PartitionName::new("A Robot Named Fight!").is_ok());
+    /// assert!(gptman::PartitionName::new("Foo\0Bar").is_err());
+    /// assert!(gptman::PartitionName::new(&"x".repeat(37)).is_err());
+    /// ```
+    pub fn new(name: &str) -> Result<PartitionName> {
+        if name.contains('\0') {
+            return Err(Error::InvalidPartitionName(
+                "contains a null character".to_string(),
+            ));
+        }
+        if !Self::fits(name) {
+            return Err(Error::InvalidPartitionName(format!(
+                "longer than {} UTF-16 code units",
+                Self::MAX_UTF16_LEN
+            )));
+        }
+
+        Ok(PartitionName(name.to_string()))
+    }
+
     /// Extracts a string slice containing the entire `PartitionName`.
     pub fn as_str(&self) -> &str {
         self.0.as_str()
     }
+
+    /// Returns the 36 UTF-16 code units of the `PartitionName` as they are written to disk.
+    ///
+    /// The name is padded with null code units (or truncated) to fit exactly 36 code units.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let name: gptman::PartitionName = "Foo".into();
+    /// let units = name.as_utf16_units();
+    ///
+    /// assert_eq!(units.len(), 36);
+    /// assert_eq!(&units[..4], &[0x46, 0x6f, 0x6f, 0x00]);
+    /// ```
+    pub fn as_utf16_units(&self) -> Vec<u16> {
+        self.0
+            .encode_utf16()
+            .chain(std::iter::repeat(0))
+            .take(Self::MAX_UTF16_LEN)
+            .collect()
+    }
+
+    /// Makes a `PartitionName` from UTF-16 code units. The name ends at the first null code unit
+    /// and invalid code units are replaced by U+FFFD (like the names read from disk).
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let name = gptman::PartitionName::from_utf16_units(&[0x46, 0x6f, 0x6f, 0x00, 0x42]);
+    ///
+    /// assert_eq!(name.as_str(), "Foo");
+    /// ```
+    pub fn from_utf16_units(units: &[u16]) -> PartitionName {
+        let end = units.iter().position(|x| *x == 0).unwrap_or(units.len());
+
+        PartitionName(String::from_utf16_lossy(&units[..end]))
+    }
+}
+
+/// Displays a GUID (in the mixed-endian representation used in the GPT) in its canonical textual
+/// form: `XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX`.
+///
+/// The GUID is displayed in lowercase with `{}` (like libuuid and util-linux do) and in uppercase
+/// with `{:X}` (like gdisk and the UEFI specification do).
+///
+/// # Examples
+///
+/// Basic usage:
+/// ```
+/// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+///     .expect("could not open disk");
+/// let gpt = gptman::GPT::find_from(&mut f)
+///     .expect("could not find GPT");
+///
+/// let guid = gptman::GuidDisplay(&gpt[1].unique_partition_guid);
+/// assert_eq!(guid.to_string(), "12880033-50d7-9e41-921c-1433db8d1f93");
+/// assert_eq!(format!("{:X}", guid), "12880033-50D7-9E41-921C-1433DB8D1F93");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct GuidDisplay<'a>(pub &'a [u8; 16]);
+
+impl GuidDisplay<'_> {
+    fn fmt_case(&self, f: &mut fmt::Formatter<'_>, uppercase: bool) -> fmt::Result {
+        let digits = if uppercase {
+            b"0123456789ABCDEF"
+        } else {
+            b"0123456789abcdef"
+        };
+        let mut text = *b"00000000-0000-0000-0000-000000000000";
+        for (x, i) in self.0.iter().zip(GUID_TEXT_POSITIONS) {
+            text[i] = digits[usize::from(x >> 4)];
+            text[i + 1] = digits[usize::from(x & 0xf)];
+        }
+
+        f.write_str(std::str::from_utf8(&text).expect("valid ASCII"))
+    }
+}
+
+impl fmt::Display for GuidDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_case(f, false)
+    }
+}
+
+impl fmt::UpperHex for GuidDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_case(f, true)
+    }
+}
+
+/// Parse a GUID in its textual form (`XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX`, in lowercase or in
+/// uppercase) to the mixed-endian representation used in the GPT. This is the reverse of
+/// [`GuidDisplay`].
+///
+/// # Errors
+///
+/// This function will return `Error::InvalidGuid` if the string is not a GUID.
+///
+/// # Examples
+///
+/// Basic usage:
+/// ```
+/// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+///     .expect("could not open disk");
+/// let mut gpt = gptman::GPT::find_from(&mut f)
+///     .expect("could not find GPT");
+///
+/// assert_eq!(
+///     gptman::parse_guid("12880033-50D7-9E41-921C-1433DB8D1F93").unwrap(),
+///     gpt[1].unique_partition_guid
+/// );
+///
+/// gpt.header.disk_guid = gptman::parse_guid("a1b2c3d4-0000-4000-8000-000000000001").unwrap();
+/// assert_eq!(
+///     gptman::GuidDisplay(&gpt.header.disk_guid).to_string(),
+///     "a1b2c3d4-0000-4000-8000-000000000001"
+/// );
+/// ```
+pub fn parse_guid(s: &str) -> Result<[u8; 16]> {
+    let bytes = s.as_bytes();
+    let is_valid = bytes.len() == 36
+        && bytes.iter().enumerate().all(|(i, c)| {
+            if [8, 13, 18, 23].contains(&i) {
+                *c == b'-'
+            } else {
+                c.is_ascii_hexdigit()
+            }
+        });
+    if !is_valid {
+        return Err(Error::InvalidGuid(s.to_string()));
+    }
+
+    let mut guid = [0; 16];
+    for (x, i) in guid.iter_mut().zip(GUID_TEXT_POSITIONS) {
+        *x = u8::from_str_radix(&s[i..i + 2], 16).expect("valid hexadecimal digits");
+    }
+
+    Ok(guid)
 }
 
 impl std::fmt::Display for PartitionName {
@@ -400,25 +894,24 @@ impl<'de> Visitor<'de> for UTF16LEVisitor {
     type Value = PartitionName;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        formatter.write_str("36 UTF-16LE code units (72 bytes)")
+        write!(
+            formatter,
+            "{} UTF-16LE code units ({} bytes)",
+            PartitionName::MAX_UTF16_LEN,
+            PartitionName::MAX_UTF16_LEN * 2
+        )
     }
 
     fn visit_seq<A>(self, mut seq: A) -> std::result::Result<PartitionName, A::Error>
     where
         A: SeqAccess<'de>,
     {
-        let mut v = Vec::new();
-        let mut end = false;
-        loop {
-            match seq.next_element()? {
-                Some(0) => end = true,
-                Some(x) if !end => v.push(x),
-                Some(_) => {}
-                None => break,
-            }
+        let mut v = Vec::with_capacity(PartitionName::MAX_UTF16_LEN);
+        while let Some(x) = seq.next_element()? {
+            v.push(x);
         }
 
-        Ok(PartitionName(String::from_utf16_lossy(&v)))
+        Ok(PartitionName::from_utf16_units(&v))
     }
 }
 
@@ -427,7 +920,7 @@ impl<'de> Deserialize<'de> for PartitionName {
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_tuple(36, UTF16LEVisitor)
+        deserializer.deserialize_tuple(PartitionName::MAX_UTF16_LEN, UTF16LEVisitor)
     }
 }
 
@@ -436,9 +929,8 @@ impl Serialize for PartitionName {
     where
         S: Serializer,
     {
-        let s = self.0.encode_utf16();
-        let mut seq = serializer.serialize_tuple(36)?;
-        for x in s.chain([0].iter().cycle().cloned()).take(36) {
+        let mut seq = serializer.serialize_tuple(PartitionName::MAX_UTF16_LEN)?;
+        for x in self.as_utf16_units() {
             seq.serialize_element(&x)?;
         }
         seq.end()
@@ -469,7 +961,7 @@ impl Serialize for PartitionName {
 ///
 /// assert_eq!(gpt[1].partition_name.as_str(), "A Robot Named Fight!");
 /// ```
-#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, Hash)]
 pub struct GPTPartitionEntry {
     /// 16 bytes representing the UUID of the partition's type.
     pub partition_type_guid: [u8; 16],
@@ -527,9 +1019,9 @@ impl GPTPartitionEntry {
     }
 
     /// Read a partition entry from the reader at the current position.
-    pub fn read_from<R: ?Sized>(reader: &mut R) -> bincode::Result<GPTPartitionEntry>
+    pub fn read_from<R>(reader: &mut R) -> bincode::Result<GPTPartitionEntry>
     where
-        R: Read,
+        R: Read + ?Sized,
     {
         deserialize_from(reader)
     }
@@ -544,6 +1036,24 @@ impl GPTPartitionEntry {
         !self.is_unused()
     }
 
+    /// Returns the unique partition GUID in its canonical textual form (lowercase). This is the
+    /// PARTUUID used by Linux (e.g. in `/dev/disk/by-partuuid/`).
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// assert_eq!(gpt[1].partuuid(), "12880033-50d7-9e41-921c-1433db8d1f93");
+    /// ```
+    pub fn partuuid(&self) -> String {
+        GuidDisplay(&self.unique_partition_guid).to_string()
+    }
+
     /// Returns the number of sectors in the partition. A partition entry must always be 1 sector
     /// long at minimum.
     ///
@@ -583,6 +1093,28 @@ impl GPTPartitionEntry {
         Ok(self.ending_lba - self.starting_lba + 1)
     }
 
+    /// Returns the size of the partition in bytes.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the `ending_lba` is lesser than the `starting_lba`
+    /// or if the computation overflows.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// assert_eq!(gpt[1].size_bytes(gpt.sector_size()).ok(), Some(10 * 512));
+    /// ```
+    pub fn size_bytes(&self, sector_size: u64) -> Result<u64> {
+        self.size()?.checked_mul(sector_size).ok_or(Error::Overflow)
+    }
+
     /// Get the range of sectors covered by a partition.
     ///
     /// # Errors
@@ -621,9 +1153,59 @@ impl GPTPartitionEntry {
     }
 }
 
+/// The validations enforced by [`GPT::read_from_with_options`]. By default, all of them are
+/// enforced (like [`GPT::read_from`] does).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadOptions {
+    /// Check the CRC32 checksum of the partition entry array.
+    pub verify_array_crc: bool,
+    /// Check the CRC32 checksum of the header. When disabled, a header with a wrong checksum is
+    /// used as is (the other checks on the header still apply).
+    pub verify_header_crc: bool,
+    /// Read the backup header if the primary header could not be read.
+    pub try_backup: bool,
+}
+
+impl Default for ReadOptions {
+    fn default() -> ReadOptions {
+        ReadOptions {
+            verify_array_crc: true,
+            verify_header_crc: true,
+            try_backup: true,
+        }
+    }
+}
+
+/// How the bytes of a disk are used, see [`GPT::disk_usage_breakdown`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskUsage {
+    /// Size (in bytes) of the protective MBR, the primary header and its partition entry array
+    /// (everything before the first usable sector).
+    pub primary_metadata: u64,
+    /// Size (in bytes) of the usable sectors that are not allocated to a partition.
+    pub free: u64,
+    /// Size (in bytes) of the sectors allocated to the partitions.
+    pub allocated: u64,
+    /// Size (in bytes) of the backup partition entry array and the backup header (everything
+    /// after the last usable sector).
+    pub backup_metadata: u64,
+}
+
+/// The headers wrote by [`GPT::write_headers_into`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrittenHeaders {
+    /// The primary header (located at the beginning of the disk).
+    pub primary: GPTHeader,
+    /// The backup header (located at the end of the disk).
+    pub backup: GPTHeader,
+}
+
 /// A type representing a GUID partition table including its partitions, the sector size of the
 /// disk and the alignment of the partitions to the sectors.
 ///
+/// A `GPT` only owns plain data (it doesn't keep a reference to the disk), thus it is `Send` and
+/// `Sync` and can be moved or shared across threads.
+///
 /// # Examples
 ///
 /// Read an existing GPT on a reader and list its partitions:
@@ -640,19 +1222,21 @@ impl GPTPartitionEntry {
 ///         println!("Partition #{}: type = {:?}, size = {} bytes, starting lba = {}",
 ///             i,
 ///             p.partition_type_guid,
-///             p.size().unwrap() * gpt.sector_size,
+///             p.size_bytes(gpt.sector_size()).unwrap(),
 ///             p.starting_lba);
 ///     }
 /// }
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub struct GPT {
-    /// Sector size of the disk.
-    ///
-    /// You should not change this, otherwise the starting locations of your partitions will be
-    /// different in bytes.
-    pub sector_size: u64,
+    sector_size: u64,
     /// GPT partition header (disk GUID, first/last usable LBA, etc...)
+    ///
+    /// Changing the locations in the header directly is not validated until the GPT is wrote (see
+    /// [`Self::verify`]). Prefer the setters like [`Self::set_first_usable_lba`],
+    /// [`Self::set_last_usable_lba`] or [`Self::set_number_of_partition_entries`] which return an
+    /// error immediately, and [`Self::header`] for read-only access. This field is public for
+    /// compatibility only and might become private in a future major version.
     pub header: GPTHeader,
     partitions: Vec<GPTPartitionEntry>,
     /// Partitions alignment (in sectors)
@@ -665,6 +1249,102 @@ pub struct GPT {
     ///
     /// The value must be greater than 0, otherwise you will encounter divisions by zero.
     pub align: u64,
+    /// Sectors of the previous partition entry arrays that must be zeroed on the next write
+    /// (after the number of partition entries has been reduced).
+    stale_partition_arrays: Vec<RangeInclusive<u64>>,
+}
+
+// NOTE: the stale partition entry arrays are only a bookkeeping for the next write, they are not
+//       compared
+impl PartialEq for GPT {
+    fn eq(&self, other: &GPT) -> bool {
+        self.sector_size == other.sector_size
+            && self.header == other.header
+            && self.partitions == other.partitions
+            && self.align == other.align
+    }
+}
+
+impl Eq for GPT {}
+
+/// Copy the data of a byte range of a reader (e.g. a partition, see
+/// [`GPT::get_partition_byte_range`]) to a byte range of a writer. The data is copied by
+/// chunks and `progress` is called after every chunk with the number of bytes copied so far
+/// and the total number of bytes to copy.
+///
+/// If the destination range is bigger than the source range, the remaining bytes of the
+/// destination are left untouched.
+///
+/// # Errors
+///
+/// This function will return `Error::NoSpaceLeft` if the destination range is smaller than
+/// the source range and `Error::InvalidPartitionBoundaries` if a range is empty. It will also
+/// return an error if the reader ends before the end of the source range.
+///
+/// # Examples
+///
+/// Basic usage:
+/// ```
+/// let src = vec![0xaa; 1024];
+/// let mut dst = std::io::Cursor::new(vec![0; 2048]);
+/// let mut last_progress = (0, 0);
+///
+/// gptman::copy_data_between(
+///     &mut std::io::Cursor::new(src),
+///     512..=1023,
+///     &mut dst,
+///     1024..=2047,
+///     |copied, total| last_progress = (copied, total),
+/// )
+/// .expect("could not copy the data");
+///
+/// assert_eq!(last_progress, (512, 512));
+/// let dst = dst.into_inner();
+/// assert!(dst[..1024].iter().all(|x| *x == 0));
+/// assert!(dst[1024..1536].iter().all(|x| *x == 0xaa));
+/// assert!(dst[1536..].iter().all(|x| *x == 0));
+/// ```
+pub fn copy_data_between<R, W, F>(
+    reader: &mut R,
+    src_range: RangeInclusive<u64>,
+    writer: &mut W,
+    dst_range: RangeInclusive<u64>,
+    mut progress: F,
+) -> Result<()>
+where
+    R: Read + Seek + ?Sized,
+    W: Write + Seek + ?Sized,
+    F: FnMut(u64, u64),
+{
+    const CHUNK_SIZE: u64 = 1024 * 1024;
+
+    let src_len = src_range
+        .end()
+        .checked_sub(*src_range.start())
+        .ok_or(Error::InvalidPartitionBoundaries)?;
+    let dst_len = dst_range
+        .end()
+        .checked_sub(*dst_range.start())
+        .ok_or(Error::InvalidPartitionBoundaries)?;
+    if dst_len < src_len {
+        return Err(Error::NoSpaceLeft);
+    }
+    let total = src_len.checked_add(1).ok_or(Error::Overflow)?;
+
+    reader.seek(SeekFrom::Start(*src_range.start()))?;
+    writer.seek(SeekFrom::Start(*dst_range.start()))?;
+
+    let mut buffer = vec![0; total.min(CHUNK_SIZE) as usize];
+    let mut copied = 0;
+    while copied < total {
+        let len = (total - copied).min(CHUNK_SIZE) as usize;
+        reader.read_exact(&mut buffer[..len])?;
+        writer.write_all(&buffer[..len])?;
+        copied += len as u64;
+        progress(copied, total);
+    }
+
+    Ok(())
 }
 
 impl GPT {
@@ -685,17 +1365,48 @@ impl GPT {
         R: Read + Seek,
     {
         let header = GPTHeader::new_from(reader, sector_size, disk_guid)?;
-        let mut partitions = Vec::with_capacity(header.number_of_partition_entries as usize);
-        for _ in 0..header.number_of_partition_entries {
-            partitions.push(GPTPartitionEntry::empty());
-        }
 
-        Ok(GPT {
-            sector_size,
-            header,
-            partitions,
-            align: DEFAULT_ALIGN,
-        })
+        Ok(Self::with_empty_partitions(sector_size, header))
+    }
+
+    /// Make a new GPT for a disk of `disk_sectors` sectors, without a reader. This is useful to
+    /// build a partition table for a disk image that doesn't exist yet (see
+    /// [`Self::write_to_image`]). (This operation does not write anything to disk!)
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the sector size is invalid or if the disk is too
+    /// small to hold the headers and the partition entry arrays with at least one usable sector.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let gpt = gptman::GPT::new(512, 100, [0xff; 16])
+    ///     .expect("could not make a partition table");
+    ///
+    /// assert_eq!(gpt.header.first_usable_lba, 34);
+    /// assert_eq!(gpt.header.last_usable_lba, 66);
+    /// assert_eq!(gpt.header.backup_lba, 99);
+    /// ```
+    pub fn new(sector_size: u64, disk_sectors: u64, disk_guid: [u8; 16]) -> Result<GPT> {
+        check_sector_size(sector_size)?;
+        let header = GPTHeader::new_with_len(disk_sectors, sector_size, disk_guid)?;
+
+        Ok(Self::with_empty_partitions(sector_size, header))
+    }
+
+    fn with_empty_partitions(sector_size: u64, header: GPTHeader) -> GPT {
+        let partitions =
+            vec![GPTPartitionEntry::empty(); header.number_of_partition_entries as usize];
+
+        GPT {
+            sector_size,
+            header,
+            partitions,
+            align: DEFAULT_ALIGN,
+            stale_partition_arrays: Vec::new(),
+        }
     }
 
     /// Read the GPT on a reader. This function will try to read the backup header if the primary
@@ -715,217 +1426,344 @@ impl GPT {
     /// let gpt = gptman::GPT::read_from(&mut f, 512)
     ///     .expect("could not read the partition table");
     /// ```
-    pub fn read_from<R: ?Sized>(mut reader: &mut R, sector_size: u64) -> Result<GPT>
+    pub fn read_from<R>(reader: &mut R, sector_size: u64) -> Result<GPT>
     where
-        R: Read + Seek,
+        R: Read + Seek + ?Sized,
     {
-        use self::Error::*;
-
-        reader.seek(SeekFrom::Start(sector_size))?;
-        let header = GPTHeader::read_from(&mut reader).or_else(|primary_err| {
-            let len = reader.seek(SeekFrom::End(0))?;
-            reader.seek(SeekFrom::Start((len / sector_size - 1) * sector_size))?;
-
-            GPTHeader::read_from(&mut reader).map_err(|backup_err| {
-                match (primary_err, backup_err) {
-                    (InvalidSignature, InvalidSignature) => InvalidSignature,
-                    (x, y) => Error::ReadError(Box::new(x), Box::new(y)),
-                }
-            })
-        })?;
-
-        let mut partitions = Vec::with_capacity(header.number_of_partition_entries as usize);
-        for i in 0..header.number_of_partition_entries {
-            reader.seek(SeekFrom::Start(
-                header.partition_entry_lba * sector_size
-                    + u64::from(i) * u64::from(header.size_of_partition_entry),
-            ))?;
-            partitions.push(GPTPartitionEntry::read_from(&mut reader)?);
-        }
+        Self::read_from_with_options(reader, sector_size, ReadOptions::default())
+    }
 
-        let sum = header.generate_partition_entry_array_crc32(&partitions);
-        if header.partition_entry_array_crc32 != sum {
-            return Err(Error::InvalidPartitionEntryArrayChecksum(
-                header.partition_entry_array_crc32,
-                sum,
-            ));
+    /// Read the GPT on a reader like `read_from` but choosing which validations are enforced (see
+    /// [`ReadOptions`]). This gives recovery tools a finer control than `read_from_lenient`.
+    ///
+    /// # Errors
+    ///
+    /// See `read_from`. The errors of the validations that are disabled are not returned.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let options = gptman::ReadOptions {
+    ///     verify_array_crc: false,
+    ///     ..Default::default()
+    /// };
+    /// let gpt = gptman::GPT::read_from_with_options(&mut f, 512, options)
+    ///     .expect("could not read the partition table");
+    /// ```
+    pub fn read_from_with_options<R>(
+        reader: &mut R,
+        sector_size: u64,
+        options: ReadOptions,
+    ) -> Result<GPT>
+    where
+        R: Read + Seek + ?Sized,
+    {
+        match Self::read_from_impl(reader, sector_size, None, false, options)? {
+            (gpt, Some(Error::InvalidPartitionEntryArrayChecksum(..)))
+                if !options.verify_array_crc =>
+            {
+                Ok(gpt)
+            }
+            (_, Some(err)) => Err(err),
+            (gpt, None) => Ok(gpt),
         }
+    }
 
-        let align = GPT::find_alignment(&header, &partitions);
-
-        Ok(GPT {
-            sector_size,
-            header,
-            partitions,
-            align,
-        })
+    /// Read only the GPT header of a reader, like `read_from` would (the backup header is read if
+    /// the primary header could not be read), without reading the partition entry array.
+    ///
+    /// This is much faster than `read_from` when only the disk GUID (or other header fields) is
+    /// needed: a single sector is read instead of the whole partition entry array. Note that the
+    /// partitions are absent and that the CRC32 checksum of the partition entry array is therefore
+    /// not checked.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the same errors as `read_from` when no GPT header could be read.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let header = gptman::GPT::read_headers_only(&mut f, 512)
+    ///     .expect("could not read the header");
+    ///
+    /// assert_eq!(header.number_of_partition_entries, 128);
+    /// ```
+    pub fn read_headers_only<R>(reader: &mut R, sector_size: u64) -> Result<GPTHeader>
+    where
+        R: Read + Seek + ?Sized,
+    {
+        Self::read_header_impl(reader, sector_size, None, false, ReadOptions::default())
     }
 
-    /// Find the GPT on a reader. This function will try to read the GPT on a disk using a sector
-    /// size of 512 but if it fails it will automatically try to read the GPT using a sector size
-    /// of 4096.
+    /// Read the GPT on a reader like `read_from` but without failing when the CRC32 checksum of
+    /// the partition entry array doesn't match or when the revision of the header is not the
+    /// standard revision (00 00 01 00).
+    ///
+    /// On success, the second value of the tuple is the
+    /// `Error::InvalidPartitionEntryArrayChecksum` or the `Error::InvalidRevision` that would have
+    /// been returned by `read_from` (the checksum error takes precedence), or `None` if the GPT is
+    /// intact. This allows recovery tools to inspect the partitions and write them back (which
+    /// will fix the checksum). The revision of the header is preserved when writing.
     ///
     /// # Examples
     ///
     /// Basic usage:
     /// ```
-    /// let mut f_512 = std::fs::File::open("tests/fixtures/disk1.img")
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
     ///     .expect("could not open disk");
-    /// let gpt_512 = gptman::GPT::find_from(&mut f_512)
+    /// let (gpt, warning) = gptman::GPT::read_from_lenient(&mut f, 512)
     ///     .expect("could not read the partition table");
     ///
-    /// let mut f_4096 = std::fs::File::open("tests/fixtures/disk2.img")
+    /// assert!(warning.is_none());
+    /// ```
+    pub fn read_from_lenient<R>(reader: &mut R, sector_size: u64) -> Result<(GPT, Option<Error>)>
+    where
+        R: Read + Seek + ?Sized,
+    {
+        Self::read_from_impl(reader, sector_size, None, true, ReadOptions::default())
+    }
+
+    /// Read a GPT on a reader using the header located at the LBA `lba` and the partition entry
+    /// array it points to. This is useful to reconstruct a GPT from a copy of a header that is
+    /// neither the primary header nor the backup header of the disk (e.g. when recovering a disk).
+    ///
+    /// Unlike `read_from`, this function doesn't fall back on another copy of the header.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is no valid header at `lba` or if the CRC32
+    /// checksum of the partition entry array doesn't match.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
     ///     .expect("could not open disk");
-    /// let gpt_4096 = gptman::GPT::find_from(&mut f_4096)
+    /// let gpt = gptman::GPT::read_from(&mut f, 512)
     ///     .expect("could not read the partition table");
+    ///
+    /// let backup = gptman::GPT::from_reader_at(&mut f, 512, gpt.header.backup_lba)
+    ///     .expect("could not read the backup partition table");
+    ///
+    /// assert!(backup.is_backup());
+    /// assert_eq!(backup[1], gpt[1]);
     /// ```
-    pub fn find_from<R: ?Sized>(mut reader: &mut R) -> Result<GPT>
+    pub fn from_reader_at<R>(mut reader: &mut R, sector_size: u64, lba: u64) -> Result<GPT>
     where
-        R: Read + Seek,
+        R: Read + Seek + ?Sized,
     {
-        use self::Error::*;
-
-        Self::read_from(&mut reader, 512).or_else(|err_at_512| match err_at_512 {
-            InvalidSignature => Self::read_from(&mut reader, 4096),
-            err => Err(err),
-        })
+        check_sector_size(sector_size)?;
+        reader.seek(SeekFrom::Start(
+            lba.checked_mul(sector_size).ok_or(Error::Overflow)?,
+        ))?;
+        let header = GPTHeader::read_from(&mut reader)?;
+
+        match Self::read_partitions_from(reader, sector_size, header)? {
+            (_, Some(err)) => Err(err),
+            (gpt, None) => Ok(gpt),
+        }
     }
 
-    fn find_alignment(header: &GPTHeader, partitions: &[GPTPartitionEntry]) -> u64 {
-        let lbas = partitions
-            .iter()
-            .filter(|x| x.is_used())
-            .map(|x| x.starting_lba)
-            .collect::<Vec<_>>();
-
-        if lbas.is_empty() {
-            return DEFAULT_ALIGN;
+    /// Read the GPT on a reader like `read_from` but use the length of the disk given in
+    /// parameter (in bytes) instead of seeking to the end of the reader to locate the backup
+    /// header. This is useful on devices where seeking to the end doesn't return the size of the
+    /// device (the size can be obtained with the ioctl `BLKGETSIZE64` on Linux).
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let gpt = gptman::GPT::read_from_with_len(&mut f, 512, 100 * 512)
+    ///     .expect("could not read the partition table");
+    ///
+    /// assert_eq!(gpt.header.backup_lba, 99);
+    /// ```
+    pub fn read_from_with_len<R>(reader: &mut R, sector_size: u64, disk_len: u64) -> Result<GPT>
+    where
+        R: Read + Seek + ?Sized,
+    {
+        match Self::read_from_impl(
+            reader,
+            sector_size,
+            Some(disk_len),
+            false,
+            ReadOptions::default(),
+        )? {
+            (_, Some(err)) => Err(err),
+            (gpt, None) => Ok(gpt),
         }
+    }
 
-        if lbas.len() == 1 && lbas[0] == header.first_usable_lba {
-            return 1;
-        }
+    fn read_from_impl<R>(
+        reader: &mut R,
+        sector_size: u64,
+        disk_len: Option<u64>,
+        any_revision: bool,
+        options: ReadOptions,
+    ) -> Result<(GPT, Option<Error>)>
+    where
+        R: Read + Seek + ?Sized,
+    {
+        let header = Self::read_header_impl(reader, sector_size, disk_len, any_revision, options)?;
 
-        (1..=MAX_ALIGN.min(*lbas.iter().max().unwrap_or(&1)))
-            .filter(|div| lbas.iter().all(|x| x % div == 0))
-            .max()
-            .unwrap()
+        Self::read_partitions_from(reader, sector_size, header)
     }
 
-    fn check_partition_guids(&self) -> Result<()> {
-        let guids: Vec<_> = self
-            .partitions
-            .iter()
-            .filter(|x| x.is_used())
-            .map(|x| x.unique_partition_guid)
-            .collect();
-        if guids.len() != guids.iter().collect::<HashSet<_>>().len() {
-            return Err(Error::ConflictPartitionGUID);
-        }
+    /// Read the primary header or, if it could not be read (and `options.try_backup` is set), the
+    /// backup header at the last sector of the disk (of `disk_len` bytes if given).
+    fn read_header_impl<R>(
+        mut reader: &mut R,
+        sector_size: u64,
+        disk_len: Option<u64>,
+        any_revision: bool,
+        options: ReadOptions,
+    ) -> Result<GPTHeader>
+    where
+        R: Read + Seek + ?Sized,
+    {
+        use self::Error::*;
 
-        Ok(())
+        check_sector_size(sector_size)?;
+        reader.seek(SeekFrom::Start(sector_size))?;
+        GPTHeader::read_from_impl(&mut reader, any_revision, options.verify_header_crc).or_else(
+            |primary_err| {
+                if !options.try_backup {
+                    return Err(primary_err);
+                }
+                let len = match disk_len {
+                    Some(len) => len,
+                    None => reader.seek(SeekFrom::End(0))?,
+                };
+                let last_lba = match (len / sector_size).checked_sub(1) {
+                    Some(lba) => lba,
+                    None => return Err(primary_err),
+                };
+                reader.seek(SeekFrom::Start(last_lba * sector_size))?;
+
+                GPTHeader::read_from_impl(&mut reader, any_revision, options.verify_header_crc)
+                    .map_err(|backup_err| match (primary_err, backup_err) {
+                        (InvalidSignature, InvalidSignature) => InvalidSignature,
+                        (x, y) => Error::ReadError(Box::new(x), Box::new(y)),
+                    })
+            },
+        )
     }
 
-    fn check_partition_boundaries(&self) -> Result<()> {
-        if self
-            .partitions
-            .iter()
-            .any(|x| x.ending_lba < x.starting_lba)
-        {
-            return Err(Error::InvalidPartitionBoundaries);
-        }
+    /// Read the partition entry array of `header` and check its CRC32 checksum. The checksum error
+    /// (or the non-standard revision of the header) is returned as the second value of the tuple.
+    fn read_partitions_from<R>(
+        mut reader: &mut R,
+        sector_size: u64,
+        header: GPTHeader,
+    ) -> Result<(GPT, Option<Error>)>
+    where
+        R: Read + Seek + ?Sized,
+    {
+        let partitions = header
+            .entries_iter(&mut reader, sector_size)
+            .collect::<Result<Vec<_>>>()?;
 
-        let mut partitions: Vec<&GPTPartitionEntry> =
-            self.partitions.iter().filter(|x| x.is_used()).collect();
-        partitions.sort_unstable_by_key(|x| x.starting_lba);
-        let first_available =
-            partitions
-                .iter()
-                .try_fold(self.header.first_usable_lba, |first_available, x| {
-                    if x.starting_lba >= first_available {
-                        Ok(x.ending_lba + 1)
-                    } else {
-                        Err(Error::InvalidPartitionBoundaries)
-                    }
-                })?;
-        if first_available > self.header.last_usable_lba + 1 {
-            return Err(Error::InvalidPartitionBoundaries);
-        }
+        let sum = header.generate_partition_entry_array_crc32(&partitions);
+        let warning = if header.partition_entry_array_crc32 != sum {
+            Some(Error::InvalidPartitionEntryArrayChecksum(
+                header.partition_entry_array_crc32,
+                sum,
+            ))
+        } else if !header.has_standard_revision() {
+            Some(Error::InvalidRevision)
+        } else {
+            None
+        };
 
-        Ok(())
+        let align = GPT::find_alignment(&header, &partitions);
+
+        Ok((
+            GPT {
+                sector_size,
+                header,
+                partitions,
+                align,
+                stale_partition_arrays: Vec::new(),
+            },
+            warning,
+        ))
     }
 
-    /// Write the GPT to a writer. This function will seek automatically in the writer to write the
-    /// primary header and the backup header at their proper location.
-    ///
-    /// Returns the backup `GPTHeader` that has been wrote in case of success (or the primary
-    /// `GPTHeader` if `self` was using a backup header).
+    /// Read the GPT on a reader like `read_from` and make sure its disk GUID is the one expected.
     ///
-    /// Note that the checksums are re-calculated, thus updating the header.
+    /// This is useful to make sure a device that has been re-opened by path is still the same
+    /// disk before doing destructive operations on it.
     ///
     /// # Errors
     ///
-    /// The partitions will be checked for consistency before being wrote to disk:
-    ///
-    /// * the partition GUIDs must be unique,
-    /// * the partitions must have positive size,
-    /// * the partitions must not overlap,
-    /// * the partitions must fit within the disk.
+    /// On top of the errors returned by `read_from`, this function will return
+    /// `Error::DiskGuidMismatch` if the disk GUID of the GPT isn't `expected_disk_guid`.
     ///
     /// # Examples
     ///
     /// Basic usage:
     /// ```
-    /// let ss = 512;
-    /// let data = vec![0; 100 * ss as usize];
-    /// let mut cur = std::io::Cursor::new(data);
-    /// let mut gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
-    ///     .expect("could not make a partition table");
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let disk_guid = gptman::GPT::read_from(&mut f, 512)
+    ///     .expect("could not read the partition table")
+    ///     .header
+    ///     .disk_guid;
     ///
-    /// // actually write:
-    /// gpt.write_into(&mut cur)
-    ///     .expect("could not write GPT to disk");
+    /// assert!(gptman::GPT::read_from_expecting_disk_guid(&mut f, 512, disk_guid).is_ok());
+    /// assert!(gptman::GPT::read_from_expecting_disk_guid(&mut f, 512, [0xff; 16]).is_err());
     /// ```
-    pub fn write_into<W: ?Sized>(&mut self, mut writer: &mut W) -> Result<GPTHeader>
+    pub fn read_from_expecting_disk_guid<R>(
+        reader: &mut R,
+        sector_size: u64,
+        expected_disk_guid: [u8; 16],
+    ) -> Result<GPT>
     where
-        W: Write + Seek,
+        R: Read + Seek + ?Sized,
     {
-        self.check_partition_guids()?;
-        self.check_partition_boundaries()?;
+        let gpt = Self::read_from(reader, sector_size)?;
 
-        let mut backup = self.header.clone();
-        backup.primary_lba = self.header.backup_lba;
-        backup.backup_lba = self.header.primary_lba;
-        backup.partition_entry_lba = if self.header.partition_entry_lba == 2 {
-            self.header.last_usable_lba + 1
-        } else {
-            2
-        };
-
-        self.header
-            .write_into(&mut writer, self.sector_size, &self.partitions)?;
-        backup.write_into(&mut writer, self.sector_size, &self.partitions)?;
+        if gpt.header.disk_guid != expected_disk_guid {
+            return Err(Error::DiskGuidMismatch {
+                expected: expected_disk_guid,
+                found: gpt.header.disk_guid,
+            });
+        }
 
-        Ok(backup)
+        Ok(gpt)
     }
 
-    /// Finds the partition where the given sector resides.
-    pub fn find_at_sector(&self, sector: u64) -> Option<u32> {
-        fn between(partition: &GPTPartitionEntry, sector: u64) -> bool {
-            sector >= partition.starting_lba && sector <= partition.ending_lba
-        }
+    /// Sector size of the disk (in bytes).
+    pub fn sector_size(&self) -> u64 {
+        self.sector_size
+    }
 
-        self.iter()
-            .find(|(_, partition)| partition.is_used() && between(partition, sector))
-            .map(|(id, _)| id)
+    /// GPT partition header (disk GUID, first/last usable LBA, etc...)
+    pub fn header(&self) -> &GPTHeader {
+        &self.header
     }
 
-    /// Find free spots in the partition table.
+    /// Change the sector size of the GPT. Nothing else is changed: the locations (in sectors) of
+    /// the headers and of the partitions are kept as is, thus their locations in bytes will be
+    /// different.
     ///
-    /// This function will return a vector of tuple with on the left: the starting LBA of the free
-    /// spot; and on the right: the size (in sectors) of the free spot.
+    /// This should only be used when the GPT has been read (or created) with the wrong sector
+    /// size.
     ///
-    /// This function will automatically align with the alignment defined in the `GPT`.
+    /// # Errors
+    ///
+    /// This function will return an error if the sector size isn't a power of two greater or
+    /// equal to 512.
     ///
     /// # Examples
     ///
@@ -934,50 +1772,26 @@ impl GPT {
     /// let ss = 512;
     /// let data = vec![0; 100 * ss as usize];
     /// let mut cur = std::io::Cursor::new(data);
-    /// let mut gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    /// let gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
     ///     .expect("could not create partition table");
     ///
-    /// gpt[1] = gptman::GPTPartitionEntry {
-    ///     partition_type_guid: [0xff; 16],
-    ///     unique_partition_guid: [0xff; 16],
-    ///     starting_lba: gpt.header.first_usable_lba + 5,
-    ///     ending_lba: gpt.header.last_usable_lba - 5,
-    ///     attribute_bits: 0,
-    ///     partition_name: "A Robot Named Fight!".into(),
-    /// };
+    /// let gpt = gpt.with_sector_size(4096).expect("invalid sector size");
+    /// assert_eq!(gpt.sector_size(), 4096);
+    /// assert!(gpt.with_sector_size(1000).is_err());
+    /// ```
+    pub fn with_sector_size(mut self, sector_size: u64) -> Result<GPT> {
+        check_sector_size(sector_size)?;
+        self.sector_size = sector_size;
+
+        Ok(self)
+    }
+
+    /// Change the alignment (in sectors) of the GPT, used to place the new partitions (see
+    /// [`Self::align`]).
     ///
-    /// // NOTE: align to the sectors, so we can use every last one of them
-    /// // NOTE: this is only for the demonstration purpose, this is not recommended
-    /// gpt.align = 1;
+    /// # Errors
     ///
-    /// assert_eq!(
-    ///     gpt.find_free_sectors(),
-    ///     vec![(gpt.header.first_usable_lba, 5), (gpt.header.last_usable_lba - 4, 5)]
-    /// );
-    /// ```
-    pub fn find_free_sectors(&self) -> Vec<(u64, u64)> {
-        assert!(self.align > 0, "align must be greater than 0");
-        let mut positions = vec![self.header.first_usable_lba - 1];
-        for partition in self.partitions.iter().filter(|x| x.is_used()) {
-            positions.push(partition.starting_lba);
-            positions.push(partition.ending_lba);
-        }
-        positions.push(self.header.last_usable_lba + 1);
-        positions.sort_unstable();
-
-        positions
-            .chunks(2)
-            .map(|x| (x[0] + 1, x[1] - x[0] - 1))
-            .filter(|(_, l)| *l > 0)
-            .map(|(i, l)| (i, l, ((i - 1) / self.align + 1) * self.align - i))
-            .map(|(i, l, s)| (i + s, l.saturating_sub(s)))
-            .filter(|(_, l)| *l > 0)
-            .collect()
-    }
-
-    /// Find the first place (most on the left) where you could start a new partition of the size
-    /// given in parameter.
-    /// This function will automatically align with the alignment defined in the `GPT`.
+    /// This function will return `Error::InvalidAlignment` if the alignment is `0`.
     ///
     /// # Examples
     ///
@@ -986,35 +1800,24 @@ impl GPT {
     /// let ss = 512;
     /// let data = vec![0; 100 * ss as usize];
     /// let mut cur = std::io::Cursor::new(data);
-    /// let mut gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
-    ///     .expect("could not create partition table");
-    ///
-    /// gpt[1] = gptman::GPTPartitionEntry {
-    ///     partition_type_guid: [0xff; 16],
-    ///     unique_partition_guid: [0xff; 16],
-    ///     starting_lba: gpt.header.first_usable_lba + 5,
-    ///     ending_lba: gpt.header.last_usable_lba - 5,
-    ///     attribute_bits: 0,
-    ///     partition_name: "A Robot Named Fight!".into(),
-    /// };
-    ///
-    /// // NOTE: align to the sectors, so we can use every last one of them
-    /// // NOTE: this is only for the demonstration purpose, this is not recommended
-    /// gpt.align = 1;
+    /// let gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    ///     .expect("could not create partition table")
+    ///     .with_align(1)
+    ///     .expect("invalid alignment");
     ///
-    /// assert_eq!(gpt.find_first_place(5), Some(gpt.header.first_usable_lba));
+    /// assert_eq!(gpt.align, 1);
+    /// assert!(gpt.with_align(0).is_err());
     /// ```
-    pub fn find_first_place(&self, size: u64) -> Option<u64> {
-        self.find_free_sectors()
-            .iter()
-            .find(|(_, l)| *l >= size)
-            .map(|(i, _)| *i)
+    pub fn with_align(mut self, align: u64) -> Result<GPT> {
+        if align == 0 {
+            return Err(Error::InvalidAlignment(align));
+        }
+        self.align = align;
+
+        Ok(self)
     }
 
-    /// Find the last place (most on the right) where you could start a new partition of the size
-    /// given in parameter.
-    ///
-    /// This function will automatically align with the alignment defined in the `GPT`.
+    /// Get the alignment of the partitions in bytes (see [`Self::align`]).
     ///
     /// # Examples
     ///
@@ -1023,391 +1826,4037 @@ impl GPT {
     /// let ss = 512;
     /// let data = vec![0; 100 * ss as usize];
     /// let mut cur = std::io::Cursor::new(data);
-    /// let mut gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    /// let gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
     ///     .expect("could not create partition table");
     ///
-    /// gpt[1] = gptman::GPTPartitionEntry {
-    ///     partition_type_guid: [0xff; 16],
-    ///     unique_partition_guid: [0xff; 16],
-    ///     starting_lba: gpt.header.first_usable_lba + 5,
-    ///     ending_lba: gpt.header.last_usable_lba - 5,
-    ///     attribute_bits: 0,
-    ///     partition_name: "A Robot Named Fight!".into(),
-    /// };
-    ///
-    /// // NOTE: align to the sectors, so we can use every last one of them
-    /// // NOTE: this is only for the demonstration purpose, this is not recommended
-    /// gpt.align = 1;
-    ///
-    /// assert_eq!(gpt.find_last_place(5), Some(gpt.header.last_usable_lba - 4));
+    /// assert_eq!(gpt.align_in_bytes(), 1024 * 1024);
     /// ```
-    pub fn find_last_place(&self, size: u64) -> Option<u64> {
-        self.find_free_sectors()
-            .iter()
-            .filter(|(_, l)| *l >= size)
-            .last()
-            .map(|(i, l)| (i + l - size) / self.align * self.align)
+    pub fn align_in_bytes(&self) -> u64 {
+        self.align.saturating_mul(self.sector_size)
     }
 
-    /// Find the most optimal place (in the smallest free space) where you could start a new
-    /// partition of the size given in parameter.
-    /// This function will automatically align with the alignment defined in the `GPT`.
+    /// Change the alignment of the partitions using a number of bytes (e.g. 1 MiB) instead of a
+    /// number of sectors (see [`Self::align`]).
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Error::InvalidAlignment` if `bytes` is `0` or is not a multiple
+    /// of the sector size. Nothing is changed in that case.
     ///
     /// # Examples
     ///
     /// Basic usage:
     /// ```
-    /// let ss = 512;
+    /// let ss = 4096;
     /// let data = vec![0; 100 * ss as usize];
     /// let mut cur = std::io::Cursor::new(data);
     /// let mut gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
     ///     .expect("could not create partition table");
     ///
-    /// gpt[1] = gptman::GPTPartitionEntry {
-    ///     partition_type_guid: [0xff; 16],
-    ///     unique_partition_guid: [0xff; 16],
-    ///     starting_lba: gpt.header.first_usable_lba + 10,
-    ///     ending_lba: gpt.header.last_usable_lba - 5,
-    ///     attribute_bits: 0,
-    ///     partition_name: "A Robot Named Fight!".into(),
-    /// };
-    ///
-    /// // NOTE: align to the sectors, so we can use every last one of them
-    /// // NOTE: this is only for the demonstration purpose, this is not recommended
-    /// gpt.align = 1;
+    /// gpt.set_align_bytes(1024 * 1024).expect("invalid alignment");
+    /// assert_eq!(gpt.align, 256);
     ///
-    /// // NOTE: the space as the end is more optimal because it will allow you to still be able to
-    /// //       insert a bigger partition later
-    /// assert_eq!(gpt.find_optimal_place(5), Some(gpt.header.last_usable_lba - 4));
+    /// assert!(gpt.set_align_bytes(512).is_err());
+    /// assert_eq!(gpt.align, 256);
     /// ```
-    pub fn find_optimal_place(&self, size: u64) -> Option<u64> {
-        let mut slots = self
-            .find_free_sectors()
-            .into_iter()
-            .filter(|(_, l)| *l >= size)
-            .collect::<Vec<_>>();
-        slots.sort_by(|(_, l1), (_, l2)| l1.cmp(l2));
-        slots.first().map(|&(i, _)| i)
+    pub fn set_align_bytes(&mut self, bytes: u64) -> Result<()> {
+        if bytes == 0 || bytes % self.sector_size != 0 {
+            return Err(Error::InvalidAlignment(bytes));
+        }
+        self.align = bytes / self.sector_size;
+
+        Ok(())
     }
 
-    /// Get the maximum size (in sectors) of a partition you could create in the GPT.
-    /// This function will automatically align with the alignment defined in the `GPT`.
+    /// Find the GPT on a reader. This function will try to read the GPT on a disk using a sector
+    /// size of 512 but if it fails it will automatically try to read the GPT using a sector size
+    /// of 4096.
+    ///
+    /// # Errors
+    ///
+    /// If no GPT header could be found, this function will return `Error::InvalidSignature` if
+    /// the disk has a protective MBR (the GPT headers are damaged) or `Error::NoPartitionTable`
+    /// otherwise (which usually means that the reader is a partition and not a whole disk).
     ///
     /// # Examples
     ///
     /// Basic usage:
     /// ```
-    /// let ss = 512;
-    /// let data = vec![0; 100 * ss as usize];
-    /// let mut cur = std::io::Cursor::new(data);
-    /// let mut gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
-    ///     .expect("could not create partition table");
-    ///
-    /// // NOTE: align to the sectors, so we can use every last one of them
-    /// // NOTE: this is only for the demonstration purpose, this is not recommended
-    /// gpt.align = 1;
+    /// let mut f_512 = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let gpt_512 = gptman::GPT::find_from(&mut f_512)
+    ///     .expect("could not read the partition table");
     ///
-    /// assert_eq!(
-    ///     gpt.get_maximum_partition_size().unwrap_or(0),
-    ///     gpt.header.last_usable_lba + 1 - gpt.header.first_usable_lba
-    /// );
+    /// let mut f_4096 = std::fs::File::open("tests/fixtures/disk2.img")
+    ///     .expect("could not open disk");
+    /// let gpt_4096 = gptman::GPT::find_from(&mut f_4096)
+    ///     .expect("could not read the partition table");
     /// ```
-    pub fn get_maximum_partition_size(&self) -> Result<u64> {
-        self.find_free_sectors()
-            .into_iter()
-            .map(|(_, l)| l / self.align * self.align)
-            .max()
-            .ok_or(Error::NoSpaceLeft)
+    pub fn find_from<R>(mut reader: &mut R) -> Result<GPT>
+    where
+        R: Read + Seek + ?Sized,
+    {
+        use self::Error::*;
+
+        Self::read_from(&mut reader, 512)
+            .or_else(|err_at_512| match err_at_512 {
+                InvalidSignature => Self::read_from(&mut reader, 4096),
+                err => Err(err),
+            })
+            .or_else(|err| match err {
+                InvalidSignature if !Self::has_protective_mbr(&mut reader)? => {
+                    Err(NoPartitionTable)
+                }
+                err => Err(err),
+            })
     }
 
-    /// Get the range of bytes covered by a partition.
-    ///
-    /// # Errors
+    /// Returns `true` if the first sector contains an MBR with at least one partition of type
+    /// `0xee` (GPT protective).
+    fn has_protective_mbr<R>(reader: &mut R) -> Result<bool>
+    where
+        R: Read + Seek + ?Sized,
+    {
+        let mut mbr = [0; 512];
+        reader.seek(SeekFrom::Start(0))?;
+        if let Err(err) = reader.read_exact(&mut mbr) {
+            return match err.kind() {
+                io::ErrorKind::UnexpectedEof => Ok(false),
+                _ => Err(err.into()),
+            };
+        }
+
+        Ok(mbr[510..] == [0x55, 0xaa] && (0..4).any(|i| mbr[446 + i * 16 + 4] == 0xee))
+    }
+
+    /// Get the alignment (in sectors) of the partitions on the disk: this is the greatest divisor
+    /// (up to 16384) of the starting LBAs of all the used partitions. This is the alignment used
+    /// to initialize the field `align` when reading a GPT.
     ///
-    /// This function will return an error if the partition number is invalid, or if
-    /// the partition is unused, or if the partition's `ending_lba` is less than its
-    /// `starting_lba`.
+    /// If there is no used partition, the default alignment (2048 sectors) is returned. If there
+    /// is only one partition at the very first usable sector, `1` is returned.
     ///
     /// # Examples
     ///
+    /// Basic usage:
     /// ```
-    /// let ss = 512;
-    /// let data = vec![0; 100 * ss as usize];
-    /// let mut cur = std::io::Cursor::new(data);
-    /// let mut gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
-    ///     .expect("could not create partition table");
-    /// gpt[1] = gptman::GPTPartitionEntry {
-    ///     partition_type_guid: [0xff; 16],
-    ///     unique_partition_guid: [0xff; 16],
-    ///     starting_lba: 2048,
-    ///     ending_lba: 2048,
-    ///     attribute_bits: 0,
-    ///     partition_name: "A Robot Named Fight!".into(),
-    /// };
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
     ///
-    /// assert_eq!(gpt.get_partition_byte_range(1).unwrap(), 1048576..=1049087);
+    /// assert_eq!(gpt.gcd_alignment(), 2);
     /// ```
-    pub fn get_partition_byte_range(&self, partition_number: u32) -> Result<RangeInclusive<u64>> {
-        if partition_number == 0 || partition_number > self.header.number_of_partition_entries {
-            return Err(Error::InvalidPartitionNumber(partition_number));
+    pub fn gcd_alignment(&self) -> u64 {
+        GPT::find_alignment(&self.header, &self.partitions)
+    }
+
+    fn find_alignment(header: &GPTHeader, partitions: &[GPTPartitionEntry]) -> u64 {
+        let lbas = partitions
+            .iter()
+            .filter(|x| x.is_used())
+            .map(|x| x.starting_lba)
+            .collect::<Vec<_>>();
+
+        if lbas.is_empty() {
+            return DEFAULT_ALIGN;
         }
-        let partition = &self[partition_number];
 
-        let sector_range = partition.range()?;
+        if lbas.len() == 1 && lbas[0] == header.first_usable_lba {
+            return 1;
+        }
 
-        let start_byte = sector_range
-            .start()
-            .checked_mul(self.sector_size)
-            .ok_or(Error::Overflow)?;
-        // Sector ranges are inclusive, so to get the position of the end byte we need
-        // to add the size of another sector, less one byte because the byte range
-        // returned from this function is also inclusive.
-        let end_byte = sector_range
-            .end()
-            .checked_mul(self.sector_size)
-            .and_then(|v| v.checked_add(self.sector_size - 1))
-            .ok_or(Error::Overflow)?;
-        Ok(start_byte..=end_byte)
-    }
+        let gcd = lbas.iter().fold(0, |acc, x| gcd(acc, *x));
+        if gcd <= MAX_ALIGN {
+            return gcd.max(1);
+        }
 
-    /// Sort the partition entries in the array by the starting LBA.
-    pub fn sort(&mut self) {
-        self.partitions
-            .sort_by(|a, b| match (a.is_used(), b.is_used()) {
-                (true, true) => a.starting_lba.cmp(&b.starting_lba),
-                (true, false) => Ordering::Less,
-                (false, true) => Ordering::Greater,
-                (false, false) => Ordering::Equal,
-            });
+        // NOTE: the greatest divisor of all the starting LBAs is the greatest divisor of their GCD
+        (1..=MAX_ALIGN).rev().find(|div| gcd % div == 0).unwrap()
     }
 
-    /// Remove a partition entry in the array.
-    ///
-    /// This is the equivalent of:
-    /// `gpt[i] = gptman::GPTPartitionEntry::empty();`
-    ///
-    /// # Errors
-    ///
-    /// This function will return an error if `i` is lesser or equal to `0` or greater than the
-    /// number of partition entries (which can be obtained in the header).
-    pub fn remove(&mut self, i: u32) -> Result<()> {
+    fn check_partition_number(&self, i: u32) -> Result<()> {
         if i == 0 || i > self.header.number_of_partition_entries {
             return Err(Error::InvalidPartitionNumber(i));
         }
 
-        self.partitions[i as usize - 1] = GPTPartitionEntry::empty();
+        Ok(())
+    }
+
+    /// Check that a partition `i` spanning from `starting_lba` to `ending_lba` would fit within
+    /// the disk and would not overlap any other partition.
+    fn check_partition_placement(&self, i: u32, starting_lba: u64, ending_lba: u64) -> Result<()> {
+        if ending_lba < starting_lba
+            || starting_lba < self.header.first_usable_lba
+            || ending_lba > self.header.last_usable_lba
+            || !self.conflicts_for(i, starting_lba, ending_lba).is_empty()
+        {
+            return Err(Error::InvalidPartitionBoundaries);
+        }
 
         Ok(())
     }
 
-    /// Remove a partition entry in the array that resides at a given sector.
+    /// Get the partition numbers of the used partitions (other than `i`) that would overlap the
+    /// partition `i` if it was moved or resized to span from `new_start` to `new_end` (inclusive).
+    /// Nothing is changed.
     ///
-    /// # Errors
+    /// # Examples
     ///
-    /// It is an error to provide a sector which does not belong to a partition.
-    pub fn remove_at_sector(&mut self, sector: u64) -> Result<()> {
-        self.remove(
-            self.find_at_sector(sector)
-                .ok_or(Error::PartitionNotFound)?,
-        )
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// assert_eq!(gpt.conflicts_for(1, 34, 47), Vec::<u32>::new());
+    /// assert_eq!(gpt.conflicts_for(1, 34, 48), vec![2]);
+    /// assert_eq!(gpt.conflicts_for(3, 40, 50), vec![1, 2]);
+    /// ```
+    pub fn conflicts_for(&self, i: u32, new_start: u64, new_end: u64) -> Vec<u32> {
+        self.iter()
+            .filter(|(j, x)| {
+                *j != i && x.is_used() && x.starting_lba <= new_end && new_start <= x.ending_lba
+            })
+            .map(|(j, _)| j)
+            .collect()
     }
 
-    /// Get an iterator over the partition entries and their index. The index always starts at 1.
-    pub fn iter(&self) -> impl Iterator<Item = (u32, &GPTPartitionEntry)> {
-        self.partitions
-            .iter()
-            .enumerate()
-            .map(|(i, x)| (i as u32 + 1, x))
+    /// Check that no used partition entry other than `i` has the unique partition GUID given.
+    fn check_unique_partition_guid(&self, i: u32, unique_partition_guid: &[u8; 16]) -> Result<()> {
+        if self.iter().any(|(j, x)| {
+            j != i && x.is_used() && &x.unique_partition_guid == unique_partition_guid
+        }) {
+            return Err(Error::ConflictPartitionGUID);
+        }
+
+        Ok(())
     }
 
-    /// Get a mutable iterator over the partition entries and their index. The index always starts
-    /// at 1.
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = (u32, &mut GPTPartitionEntry)> {
-        self.partitions
-            .iter_mut()
-            .enumerate()
-            .map(|(i, x)| (i as u32 + 1, x))
+    fn check_partition_guids(&self) -> Result<()> {
+        if self.used_unique_guids().len() != self.used_partition_count() as usize {
+            return Err(Error::ConflictPartitionGUID);
+        }
+
+        Ok(())
     }
 
-    /// This function writes a protective MBR in the first sector of the disk
-    /// starting at byte 446 and ending at byte 511. Any existing data will be overwritten.
+    fn check_partition_boundaries(&self) -> Result<()> {
+        if self
+            .partitions
+            .iter()
+            .any(|x| x.ending_lba < x.starting_lba)
+        {
+            return Err(Error::InvalidPartitionBoundaries);
+        }
+        if let Some((i, _)) = self
+            .iter()
+            .find(|(_, x)| x.is_used() && x.starting_lba < self.header.first_usable_lba)
+        {
+            return Err(Error::PartitionBeforeFirstUsableLba(i));
+        }
+
+        let mut partitions: Vec<&GPTPartitionEntry> =
+            self.partitions.iter().filter(|x| x.is_used()).collect();
+        partitions.sort_unstable_by_key(|x| x.starting_lba);
+        let first_available =
+            partitions
+                .iter()
+                .try_fold(self.header.first_usable_lba, |first_available, x| {
+                    if x.starting_lba >= first_available {
+                        Ok(x.ending_lba + 1)
+                    } else {
+                        Err(Error::InvalidPartitionBoundaries)
+                    }
+                })?;
+        if first_available > self.header.last_usable_lba + 1 {
+            return Err(Error::InvalidPartitionBoundaries);
+        }
+
+        Ok(())
+    }
+
+    /// Check the partitions for consistency. This is what `write_into` checks before writing
+    /// anything to disk but it can be called right after reading a GPT (for example to detect a
+    /// disk that has been cloned with its partition GUIDs).
     ///
-    /// See also: [`Self::write_bootable_protective_mbr_into`].
-    pub fn write_protective_mbr_into<W: ?Sized>(mut writer: &mut W, sector_size: u64) -> Result<()>
+    /// # Errors
+    ///
+    /// This function will return an error if one of these rules is not respected:
+    ///
+    /// * the partition GUIDs must be unique,
+    /// * the partitions must have positive size,
+    /// * the partitions must not overlap,
+    /// * the partitions must fit within the disk (`Error::PartitionBeforeFirstUsableLba` is returned
+    ///   if a partition starts before the first usable LBA),
+    /// * the partition entry arrays must be located between their header and the usable sectors.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let mut gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// assert!(gpt.verify().is_ok());
+    ///
+    /// gpt[2].unique_partition_guid = gpt[1].unique_partition_guid;
+    ///
+    /// assert!(matches!(gpt.verify(), Err(gptman::Error::ConflictPartitionGUID)));
+    /// ```
+    pub fn verify(&self) -> Result<()> {
+        self.check_partition_guids()?;
+        self.check_partition_boundaries()?;
+        self.check_partition_entry_arrays()?;
+
+        Ok(())
+    }
+
+    /// Check that the primary partition entry array is located between the primary header and the
+    /// first usable sector, and that the backup partition entry array is located between the last
+    /// usable sector and the backup header.
+    fn check_partition_entry_arrays(&self) -> Result<()> {
+        let (primary_lba, backup_lba) = if self.header.is_primary() {
+            (self.header.primary_lba, self.header.backup_lba)
+        } else {
+            (self.header.backup_lba, self.header.primary_lba)
+        };
+        let sectors = self.partition_array_sectors();
+        let primary_partition_entry_lba = self.primary_partition_entry_lba();
+        let backup_partition_entry_lba = self.backup_partition_entry_lba();
+
+        if primary_partition_entry_lba <= primary_lba
+            || primary_partition_entry_lba.saturating_add(sectors) > self.header.first_usable_lba
+            || backup_partition_entry_lba <= self.header.last_usable_lba
+            || backup_partition_entry_lba.saturating_add(sectors) > backup_lba
+        {
+            return Err(Error::InvalidPartitionBoundaries);
+        }
+
+        Ok(())
+    }
+
+    /// Move the partition entry array of the backup header right before the backup header (like
+    /// [`Self::write_into`] does when the GPT uses the primary header). This only changes something
+    /// if the GPT uses the backup header. Returns `true` if the location has been changed.
+    ///
+    /// Some malformed partition tables have a backup partition entry array that overlaps the usable
+    /// sectors or the backup header itself (see [`Self::verify`]). Once fixed, writing the GPT
+    /// produces a layout that is conform to the specification.
+    ///
+    /// Note that the array is placed at `backup_lba - partition_array_sectors` rather than at
+    /// `last_usable_lba + 1`: both are the same on a standard layout, but the former keeps the
+    /// sectors reserved at the end of the disk (see [`Self::set_last_usable_lba`]) out of the
+    /// array.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let mut gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// // NOTE: using the primary header, the backup partition entry array is always placed
+    /// //       right before the backup header
+    /// assert!(!gpt.fix_backup_array_lba());
+    /// ```
+    pub fn fix_backup_array_lba(&mut self) -> bool {
+        if self.header.is_primary() {
+            return false;
+        }
+
+        let partition_entry_lba = self
+            .header
+            .primary_lba
+            .saturating_sub(self.partition_array_sectors());
+        let changed = self.header.partition_entry_lba != partition_entry_lba;
+        self.header.partition_entry_lba = partition_entry_lba;
+
+        changed
+    }
+
+    /// Write the GPT to a writer. This function will seek automatically in the writer to write the
+    /// primary header and the backup header at their proper location.
+    ///
+    /// Returns the backup `GPTHeader` that has been wrote in case of success (or the primary
+    /// `GPTHeader` if `self` was using a backup header).
+    ///
+    /// Note that the checksums are re-calculated, thus updating the header.
+    ///
+    /// # Errors
+    ///
+    /// The partitions will be checked for consistency before being wrote to disk (see
+    /// [`Self::verify`]). This function will return `Error::DiskTooSmall` if the backup header
+    /// would be located after the end of the disk. The length of the disk is obtained by seeking
+    /// to the end of the writer: use [`Self::write_into_with_len`] if the writer doesn't report
+    /// its length (a block device that returns `0`) or if it is expected to grow (a new file).
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let ss = 512;
+    /// let data = vec![0; 100 * ss as usize];
+    /// let mut cur = std::io::Cursor::new(data);
+    /// let mut gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    ///     .expect("could not make a partition table");
+    ///
+    /// // actually write:
+    /// gpt.write_into(&mut cur)
+    ///     .expect("could not write GPT to disk");
+    /// ```
+    pub fn write_into<W>(&mut self, writer: &mut W) -> Result<GPTHeader>
     where
-        W: Write + Seek,
+        W: Write + Seek + ?Sized,
     {
-        Self::write_protective_mbr_into_impl(&mut writer, sector_size, false)
+        let headers = self.write_headers_into(writer)?;
+
+        Ok(self.other_header(headers))
+    }
+
+    /// Write the GPT to a writer like [`Self::write_into`] but use the length of the disk given
+    /// in argument (in bytes) instead of seeking to the end of the writer. This is useful for
+    /// block devices which don't report their size when seeking to their end, or for files that
+    /// grow while being wrote.
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::write_into`]. `Error::DiskTooSmall` is returned if the backup header would be
+    /// located after `disk_len`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let ss = 512;
+    /// let mut cur = std::io::Cursor::new(Vec::new());
+    /// let mut gpt = gptman::GPT::new(ss, 100, [0xff; 16])
+    ///     .expect("could not make a partition table");
+    ///
+    /// assert!(matches!(
+    ///     gpt.write_into_with_len(&mut cur, 99 * ss),
+    ///     Err(gptman::Error::DiskTooSmall { .. })
+    /// ));
+    /// gpt.write_into_with_len(&mut cur, 100 * ss)
+    ///     .expect("could not write GPT to disk");
+    /// ```
+    pub fn write_into_with_len<W>(&mut self, writer: &mut W, disk_len: u64) -> Result<GPTHeader>
+    where
+        W: Write + Seek + ?Sized,
+    {
+        let headers = self.write_headers_impl(writer, false, Some(disk_len))?;
+
+        Ok(self.other_header(headers))
+    }
+
+    /// The header of `headers` that is not `self.header`.
+    fn other_header(&self, headers: WrittenHeaders) -> GPTHeader {
+        if self.header.is_primary() {
+            headers.backup
+        } else {
+            headers.primary
+        }
+    }
+
+    /// Write the GPT to a writer like [`Self::write_into`] but return both the primary and the
+    /// backup headers that have been wrote. This allows verifying what has been committed to the
+    /// disk without reading it again.
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::write_into`].
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let ss = 512;
+    /// let data = vec![0; 100 * ss as usize];
+    /// let mut cur = std::io::Cursor::new(data);
+    /// let mut gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    ///     .expect("could not make a partition table");
+    ///
+    /// let headers = gpt.write_headers_into(&mut cur)
+    ///     .expect("could not write GPT to disk");
+    ///
+    /// assert_eq!(headers.primary, gpt.header);
+    /// assert_eq!(headers.primary.primary_lba, 1);
+    /// assert_eq!(headers.backup.primary_lba, 99);
+    /// ```
+    pub fn write_headers_into<W>(&mut self, writer: &mut W) -> Result<WrittenHeaders>
+    where
+        W: Write + Seek + ?Sized,
+    {
+        self.write_headers_impl(writer, false, None)
+    }
+
+    /// Write the GPT to a writer like [`Self::write_headers_into`] but write the backup partition
+    /// entry array and the backup header first, flush the writer, then write the primary ones (and
+    /// flush again).
+    ///
+    /// This is safer when repairing a disk with a damaged primary header: if the write is
+    /// interrupted (e.g. a power loss) while the primary copy is wrote, the backup copy at the end
+    /// of the disk is already complete and can be used to read the GPT (see [`Self::read_from`]).
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::write_into`].
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut cur = std::io::Cursor::new(std::fs::read("tests/fixtures/disk1.img").unwrap());
+    /// let mut gpt = gptman::GPT::find_from(&mut cur)
+    ///     .expect("could not find GPT");
+    ///
+    /// let headers = gpt.write_backup_first_into(&mut cur)
+    ///     .expect("could not write GPT to disk");
+    ///
+    /// assert_eq!(headers.backup.primary_lba, 99);
+    /// ```
+    pub fn write_backup_first_into<W>(&mut self, writer: &mut W) -> Result<WrittenHeaders>
+    where
+        W: Write + Seek + ?Sized,
+    {
+        self.write_headers_impl(writer, true, None)
+    }
+
+    fn write_headers_impl<W>(
+        &mut self,
+        mut writer: &mut W,
+        backup_first: bool,
+        disk_len: Option<u64>,
+    ) -> Result<WrittenHeaders>
+    where
+        W: Write + Seek + ?Sized,
+    {
+        self.verify()?;
+
+        let required = self
+            .backup_header_byte_offset()?
+            .max(self.primary_header_byte_offset()?)
+            .checked_add(self.sector_size)
+            .ok_or(Error::Overflow)?;
+        let len = match disk_len {
+            Some(len) => len,
+            None => writer.seek(SeekFrom::End(0))?,
+        };
+        if len < required {
+            return Err(Error::DiskTooSmall {
+                required,
+                found: len,
+            });
+        }
+
+        let mut alternate = self.header.clone();
+        alternate.primary_lba = self.header.backup_lba;
+        alternate.backup_lba = self.header.primary_lba;
+        alternate.partition_entry_lba = self.alternate_partition_entry_lba();
+
+        // NOTE: the stale partition entry arrays are only zeroed once the first copy is complete,
+        //       otherwise an interrupted write would leave no valid copy on the disk
+        if backup_first && self.header.is_primary() {
+            alternate.write_into(&mut writer, self.sector_size, &self.partitions)?;
+            writer.flush()?;
+            self.zero_stale_partition_arrays(&mut writer)?;
+            self.header
+                .write_into(&mut writer, self.sector_size, &self.partitions)?;
+        } else {
+            self.header
+                .write_into(&mut writer, self.sector_size, &self.partitions)?;
+            if backup_first {
+                writer.flush()?;
+            }
+            self.zero_stale_partition_arrays(&mut writer)?;
+            alternate.write_into(&mut writer, self.sector_size, &self.partitions)?;
+        }
+        if backup_first {
+            writer.flush()?;
+        }
+
+        Ok(if self.header.is_primary() {
+            WrittenHeaders {
+                primary: self.header.clone(),
+                backup: alternate,
+            }
+        } else {
+            WrittenHeaders {
+                primary: alternate,
+                backup: self.header.clone(),
+            }
+        })
+    }
+
+    /// Write a whole disk image of `disk_sectors` sectors to a writer that doesn't need to
+    /// implement `Seek` (a pipe, a network stream, ...). The image is built in memory: it contains
+    /// a protective MBR, the primary header and the backup header with their partition entry
+    /// arrays, and the remaining sectors are filled with zeroes.
+    ///
+    /// The header is first updated to fit the image (see [`GPTHeader::update_from`]), then the GPT
+    /// is written like with [`Self::write_into`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the image is too big to fit in memory, if the
+    /// partitions don't fit in the image or if they are not consistent (see [`Self::verify`]).
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let ss = 512;
+    /// let data = vec![0; 100 * ss as usize];
+    /// let mut cur = std::io::Cursor::new(data);
+    /// let mut gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    ///     .expect("could not make a partition table");
+    ///
+    /// let mut image = Vec::new();
+    /// gpt.write_to_image(200, &mut image)
+    ///     .expect("could not write the image");
+    ///
+    /// assert_eq!(image.len(), 200 * ss);
+    /// assert_eq!(gpt.header.backup_lba, 199);
+    /// ```
+    pub fn write_to_image<W>(&mut self, disk_sectors: u64, writer: &mut W) -> Result<GPTHeader>
+    where
+        W: Write + ?Sized,
+    {
+        let len = disk_sectors
+            .checked_mul(self.sector_size)
+            .and_then(|x| usize::try_from(x).ok())
+            .ok_or(Error::Overflow)?;
+        let mut cur = io::Cursor::new(vec![0; len]);
+
+        self.header.update_from(&mut cur, self.sector_size)?;
+        Self::write_protective_mbr_into(&mut cur, self.sector_size)?;
+        let backup = self.write_into(&mut cur)?;
+        writer.write_all(cur.get_ref())?;
+
+        Ok(backup)
+    }
+
+    /// Finds the partition where the given sector resides.
+    pub fn find_at_sector(&self, sector: u64) -> Option<u32> {
+        fn between(partition: &GPTPartitionEntry, sector: u64) -> bool {
+            sector >= partition.starting_lba && sector <= partition.ending_lba
+        }
+
+        self.iter()
+            .find(|(_, partition)| partition.is_used() && between(partition, sector))
+            .map(|(id, _)| id)
+    }
+
+    /// Check if the sectors from `start_lba` to `end_lba` (inclusive) are all usable and not
+    /// allocated to a partition.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// assert!(gpt.is_range_free(44, 47));
+    /// assert!(!gpt.is_range_free(44, 48));
+    /// assert!(!gpt.is_range_free(60, 67));
+    /// assert!(!gpt.is_range_free(47, 44));
+    /// ```
+    pub fn is_range_free(&self, start_lba: u64, end_lba: u64) -> bool {
+        start_lba <= end_lba
+            && start_lba >= self.header.first_usable_lba
+            && end_lba <= self.header.last_usable_lba
+            && !self
+                .partitions
+                .iter()
+                .any(|x| x.is_used() && x.starting_lba <= end_lba && start_lba <= x.ending_lba)
+    }
+
+    /// Finds the partition number of a used partition entry by its unique partition GUID (which is
+    /// the stable identifier of a partition). The other fields of `entry` are not compared.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// let entry = gpt.iter().map(|(_, x)| x).find(|x| x.partition_name.as_str() == "Bar")
+    ///     .unwrap();
+    ///
+    /// assert_eq!(gpt.partition_index_of(entry), Some(2));
+    /// assert_eq!(gpt.partition_index_of(&gptman::GPTPartitionEntry::empty()), None);
+    /// ```
+    pub fn partition_index_of(&self, entry: &GPTPartitionEntry) -> Option<u32> {
+        self.iter()
+            .find(|(_, x)| x.is_used() && x.unique_partition_guid == entry.unique_partition_guid)
+            .map(|(i, _)| i)
+    }
+
+    /// Find free spots in the partition table.
+    ///
+    /// This function will return a vector of tuple with on the left: the starting LBA of the free
+    /// spot; and on the right: the size (in sectors) of the free spot.
+    ///
+    /// This function will automatically align with the alignment defined in the `GPT`.
+    ///
+    /// If the usable sectors of the header are invalid (`first_usable_lba` is `0`), no free spot
+    /// is returned.
+    ///
+    /// The used partitions that have no valid size (their ending LBA is lower than their starting
+    /// LBA) are ignored; [`Self::verify`] reports them.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let ss = 512;
+    /// let data = vec![0; 100 * ss as usize];
+    /// let mut cur = std::io::Cursor::new(data);
+    /// let mut gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    ///     .expect("could not create partition table");
+    ///
+    /// gpt[1] = gptman::GPTPartitionEntry {
+    ///     partition_type_guid: [0xff; 16],
+    ///     unique_partition_guid: [0xff; 16],
+    ///     starting_lba: gpt.header.first_usable_lba + 5,
+    ///     ending_lba: gpt.header.last_usable_lba - 5,
+    ///     attribute_bits: 0,
+    ///     partition_name: "A Robot Named Fight!".into(),
+    /// };
+    ///
+    /// // NOTE: align to the sectors, so we can use every last one of them
+    /// // NOTE: this is only for the demonstration purpose, this is not recommended
+    /// gpt.align = 1;
+    ///
+    /// assert_eq!(
+    ///     gpt.find_free_sectors(),
+    ///     vec![(gpt.header.first_usable_lba, 5), (gpt.header.last_usable_lba - 4, 5)]
+    /// );
+    /// ```
+    pub fn find_free_sectors(&self) -> Vec<(u64, u64)> {
+        assert!(self.align > 0, "align must be greater than 0");
+        // NOTE: the LBA 0 is the protective MBR, a header with such a first usable LBA is invalid
+        if self.header.first_usable_lba == 0 || self.header.last_usable_lba == u64::MAX {
+            return Vec::new();
+        }
+        let mut positions = vec![self.header.first_usable_lba - 1];
+        for partition in self
+            .partitions
+            .iter()
+            .filter(|x| x.is_used() && x.size().is_ok())
+        {
+            positions.push(partition.starting_lba);
+            positions.push(partition.ending_lba);
+        }
+        positions.push(self.header.last_usable_lba + 1);
+        positions.sort_unstable();
+
+        positions
+            .chunks(2)
+            .map(|x| (x[0] + 1, x[1] - x[0] - 1))
+            .filter(|(_, l)| *l > 0)
+            .map(|(i, l)| (i, l, ((i - 1) / self.align + 1) * self.align - i))
+            .map(|(i, l, s)| (i + s, l.saturating_sub(s)))
+            .filter(|(_, l)| *l > 0)
+            .collect()
+    }
+
+    /// Find free spots in the partition table, like [`Self::find_free_sectors`], but the length of
+    /// each spot is also aligned: only the aligned sectors that can actually be allocated are
+    /// counted and the spots that are too small are dropped.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let ss = 512;
+    /// let data = vec![0; 100 * ss as usize];
+    /// let mut cur = std::io::Cursor::new(data);
+    /// let mut gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    ///     .expect("could not create partition table");
+    ///
+    /// gpt[1] = gptman::GPTPartitionEntry {
+    ///     partition_type_guid: [0xff; 16],
+    ///     unique_partition_guid: [0xff; 16],
+    ///     starting_lba: 44,
+    ///     ending_lba: 56,
+    ///     attribute_bits: 0,
+    ///     partition_name: "A Robot Named Fight!".into(),
+    /// };
+    ///
+    /// gpt.align = 4;
+    ///
+    /// assert_eq!(gpt.find_free_sectors(), vec![(36, 8), (60, 7)]);
+    /// assert_eq!(gpt.find_free_sectors_aligned(), vec![(36, 8), (60, 4)]);
+    /// ```
+    pub fn find_free_sectors_aligned(&self) -> Vec<(u64, u64)> {
+        self.find_free_sectors()
+            .into_iter()
+            .map(|(i, l)| (i, l / self.align * self.align))
+            .filter(|(_, l)| *l > 0)
+            .collect()
+    }
+
+    /// Find the first place (most on the left) where you could start a new partition of the size
+    /// given in parameter.
+    /// This function will automatically align with the alignment defined in the `GPT`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let ss = 512;
+    /// let data = vec![0; 100 * ss as usize];
+    /// let mut cur = std::io::Cursor::new(data);
+    /// let mut gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    ///     .expect("could not create partition table");
+    ///
+    /// gpt[1] = gptman::GPTPartitionEntry {
+    ///     partition_type_guid: [0xff; 16],
+    ///     unique_partition_guid: [0xff; 16],
+    ///     starting_lba: gpt.header.first_usable_lba + 5,
+    ///     ending_lba: gpt.header.last_usable_lba - 5,
+    ///     attribute_bits: 0,
+    ///     partition_name: "A Robot Named Fight!".into(),
+    /// };
+    ///
+    /// // NOTE: align to the sectors, so we can use every last one of them
+    /// // NOTE: this is only for the demonstration purpose, this is not recommended
+    /// gpt.align = 1;
+    ///
+    /// assert_eq!(gpt.find_first_place(5), Some(gpt.header.first_usable_lba));
+    /// ```
+    pub fn find_first_place(&self, size: u64) -> Option<u64> {
+        self.find_free_sectors()
+            .iter()
+            .find(|(_, l)| *l >= size)
+            .map(|(i, _)| *i)
+    }
+
+    /// Find the last place (most on the right) where you could start a new partition of the size
+    /// given in parameter.
+    ///
+    /// This function will automatically align with the alignment defined in the `GPT`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let ss = 512;
+    /// let data = vec![0; 100 * ss as usize];
+    /// let mut cur = std::io::Cursor::new(data);
+    /// let mut gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    ///     .expect("could not create partition table");
+    ///
+    /// gpt[1] = gptman::GPTPartitionEntry {
+    ///     partition_type_guid: [0xff; 16],
+    ///     unique_partition_guid: [0xff; 16],
+    ///     starting_lba: gpt.header.first_usable_lba + 5,
+    ///     ending_lba: gpt.header.last_usable_lba - 5,
+    ///     attribute_bits: 0,
+    ///     partition_name: "A Robot Named Fight!".into(),
+    /// };
+    ///
+    /// // NOTE: align to the sectors, so we can use every last one of them
+    /// // NOTE: this is only for the demonstration purpose, this is not recommended
+    /// gpt.align = 1;
+    ///
+    /// assert_eq!(gpt.find_last_place(5), Some(gpt.header.last_usable_lba - 4));
+    /// ```
+    pub fn find_last_place(&self, size: u64) -> Option<u64> {
+        self.find_free_sectors()
+            .iter()
+            .rfind(|(_, l)| *l >= size)
+            .map(|(i, l)| (i + l - size) / self.align * self.align)
+    }
+
+    /// Find the most optimal place (in the smallest free space) where you could start a new
+    /// partition of the size given in parameter.
+    /// This function will automatically align with the alignment defined in the `GPT`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let ss = 512;
+    /// let data = vec![0; 100 * ss as usize];
+    /// let mut cur = std::io::Cursor::new(data);
+    /// let mut gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    ///     .expect("could not create partition table");
+    ///
+    /// gpt[1] = gptman::GPTPartitionEntry {
+    ///     partition_type_guid: [0xff; 16],
+    ///     unique_partition_guid: [0xff; 16],
+    ///     starting_lba: gpt.header.first_usable_lba + 10,
+    ///     ending_lba: gpt.header.last_usable_lba - 5,
+    ///     attribute_bits: 0,
+    ///     partition_name: "A Robot Named Fight!".into(),
+    /// };
+    ///
+    /// // NOTE: align to the sectors, so we can use every last one of them
+    /// // NOTE: this is only for the demonstration purpose, this is not recommended
+    /// gpt.align = 1;
+    ///
+    /// // NOTE: the space as the end is more optimal because it will allow you to still be able to
+    /// //       insert a bigger partition later
+    /// assert_eq!(gpt.find_optimal_place(5), Some(gpt.header.last_usable_lba - 4));
+    /// ```
+    pub fn find_optimal_place(&self, size: u64) -> Option<u64> {
+        let mut slots = self
+            .find_free_sectors()
+            .into_iter()
+            .filter(|(_, l)| *l >= size)
+            .collect::<Vec<_>>();
+        slots.sort_by_key(|(_, l)| *l);
+        slots.first().map(|&(i, _)| i)
+    }
+
+    /// Get the maximum size (in sectors) of a partition you could create in the GPT.
+    /// This function will automatically align with the alignment defined in the `GPT`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let ss = 512;
+    /// let data = vec![0; 100 * ss as usize];
+    /// let mut cur = std::io::Cursor::new(data);
+    /// let mut gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    ///     .expect("could not create partition table");
+    ///
+    /// // NOTE: align to the sectors, so we can use every last one of them
+    /// // NOTE: this is only for the demonstration purpose, this is not recommended
+    /// gpt.align = 1;
+    ///
+    /// assert_eq!(
+    ///     gpt.get_maximum_partition_size().unwrap_or(0),
+    ///     gpt.header.last_usable_lba + 1 - gpt.header.first_usable_lba
+    /// );
+    /// ```
+    pub fn get_maximum_partition_size(&self) -> Result<u64> {
+        self.find_free_sectors()
+            .into_iter()
+            .map(|(_, l)| l / self.align * self.align)
+            .max()
+            .ok_or(Error::NoSpaceLeft)
+    }
+
+    /// Get the number of sectors a partition could grow by, using the free space that directly
+    /// follows it.
+    ///
+    /// This function will automatically align with the alignment defined in the `GPT`: the sector
+    /// following the (grown) partition is aligned, unless the partition can't grow at all.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the partition number is invalid, or if the partition
+    /// is unused, or if the partition's `ending_lba` is less than its `starting_lba`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let ss = 512;
+    /// let data = vec![0; 100 * ss as usize];
+    /// let mut cur = std::io::Cursor::new(data);
+    /// let mut gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    ///     .expect("could not create partition table");
+    ///
+    /// gpt[1] = gptman::GPTPartitionEntry {
+    ///     partition_type_guid: [0xff; 16],
+    ///     unique_partition_guid: [0xff; 16],
+    ///     starting_lba: gpt.header.first_usable_lba,
+    ///     ending_lba: gpt.header.first_usable_lba + 4,
+    ///     attribute_bits: 0,
+    ///     partition_name: "A Robot Named Fight!".into(),
+    /// };
+    ///
+    /// // NOTE: align to the sectors, so we can use every last one of them
+    /// // NOTE: this is only for the demonstration purpose, this is not recommended
+    /// gpt.align = 1;
+    ///
+    /// assert_eq!(
+    ///     gpt.max_growth(1).unwrap(),
+    ///     gpt.header.last_usable_lba - gpt[1].ending_lba
+    /// );
+    /// ```
+    pub fn max_growth(&self, i: u32) -> Result<u64> {
+        assert!(self.align > 0, "align must be greater than 0");
+        self.check_partition_number(i)?;
+        let ending_lba = *self[i].range()?.end();
+
+        let next_starting_lba = self
+            .partitions
+            .iter()
+            .filter(|x| x.is_used() && x.size().is_ok() && x.starting_lba > ending_lba)
+            .map(|x| x.starting_lba)
+            .min()
+            .unwrap_or(self.header.last_usable_lba + 1);
+
+        Ok((next_starting_lba / self.align * self.align).saturating_sub(ending_lba + 1))
+    }
+
+    /// Resize a partition: its starting LBA is kept and its ending LBA is moved so the partition
+    /// is `new_size` sectors long. The data of the partition is not touched.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the partition number is invalid or if the partition
+    /// is unused. It will return `Error::InvalidPartitionBoundaries` if the new size is `0`, if the
+    /// partition would overlap the next partition or if it would end after the last usable
+    /// sector. Nothing is changed in that case.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let mut gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// let max_size = gpt.max_growth(1).unwrap() + gpt[1].size().unwrap();
+    /// assert!(gpt.resize_partition(1, max_size + 1).is_err());
+    /// assert!(gpt.resize_partition(1, 0).is_err());
+    ///
+    /// gpt.resize_partition(1, max_size).expect("could not resize partition");
+    /// assert_eq!(gpt[1].size().unwrap(), max_size);
+    /// assert_eq!(gpt[1].ending_lba + 1, gpt[2].starting_lba);
+    /// ```
+    pub fn resize_partition(&mut self, i: u32, new_size: u64) -> Result<()> {
+        self.check_partition_number(i)?;
+        if self[i].is_unused() {
+            return Err(Error::UnusedPartition);
+        }
+        if new_size == 0 {
+            return Err(Error::InvalidPartitionBoundaries);
+        }
+
+        let starting_lba = self[i].starting_lba;
+        let ending_lba = starting_lba
+            .checked_add(new_size - 1)
+            .ok_or(Error::Overflow)?;
+        self.check_partition_placement(i, starting_lba, ending_lba)?;
+
+        self[i].ending_lba = ending_lba;
+
+        Ok(())
+    }
+
+    /// Get the range of bytes covered by a partition.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the partition number is invalid, or if
+    /// the partition is unused, or if the partition's `ending_lba` is less than its
+    /// `starting_lba`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let ss = 512;
+    /// let data = vec![0; 100 * ss as usize];
+    /// let mut cur = std::io::Cursor::new(data);
+    /// let mut gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    ///     .expect("could not create partition table");
+    /// gpt[1] = gptman::GPTPartitionEntry {
+    ///     partition_type_guid: [0xff; 16],
+    ///     unique_partition_guid: [0xff; 16],
+    ///     starting_lba: 2048,
+    ///     ending_lba: 2048,
+    ///     attribute_bits: 0,
+    ///     partition_name: "A Robot Named Fight!".into(),
+    /// };
+    ///
+    /// assert_eq!(gpt.get_partition_byte_range(1).unwrap(), 1048576..=1049087);
+    /// ```
+    pub fn get_partition_byte_range(&self, partition_number: u32) -> Result<RangeInclusive<u64>> {
+        self.check_partition_number(partition_number)?;
+        let partition = &self[partition_number];
+
+        let sector_range = partition.range()?;
+
+        let start_byte = sector_range
+            .start()
+            .checked_mul(self.sector_size)
+            .ok_or(Error::Overflow)?;
+        // Sector ranges are inclusive, so to get the position of the end byte we need
+        // to add the size of another sector, less one byte because the byte range
+        // returned from this function is also inclusive.
+        let end_byte = sector_range
+            .end()
+            .checked_mul(self.sector_size)
+            .and_then(|v| v.checked_add(self.sector_size - 1))
+            .ok_or(Error::Overflow)?;
+        Ok(start_byte..=end_byte)
+    }
+
+    /// Get the length of the disk (in sectors) according to the header: this is the location of
+    /// the backup header (which is the last sector of the disk) plus one.
+    ///
+    /// Note that this reflects the view of the header which may lag behind the actual size of a
+    /// disk that has been resized.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let ss = 512;
+    /// let data = vec![0; 100 * ss as usize];
+    /// let mut cur = std::io::Cursor::new(data);
+    /// let gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    ///     .expect("could not create partition table");
+    ///
+    /// assert_eq!(gpt.disk_len_sectors(), 100);
+    /// ```
+    pub fn disk_len_sectors(&self) -> u64 {
+        if self.header.is_primary() {
+            self.header.backup_lba + 1
+        } else {
+            self.header.primary_lba + 1
+        }
+    }
+
+    /// Returns `true` if the disk is bigger than the disk described by the header (e.g. after the
+    /// disk has been resized). In that case the GPT can be grown to fill the disk with
+    /// [`GPTHeader::update_from`].
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// assert!(!gpt.reader_is_larger_than_header(&mut f).unwrap());
+    ///
+    /// let mut bigger_disk = std::io::Cursor::new(vec![0; 200 * 512]);
+    /// assert!(gpt.reader_is_larger_than_header(&mut bigger_disk).unwrap());
+    /// ```
+    pub fn reader_is_larger_than_header<S>(&self, seeker: &mut S) -> Result<bool>
+    where
+        S: Seek + ?Sized,
+    {
+        let len = seeker.seek(SeekFrom::End(0))? / self.sector_size;
+
+        Ok(len > self.disk_len_sectors())
+    }
+
+    /// Check that the GPT matches the actual size of the disk: the backup header must be located
+    /// in the last sector of the disk and the last usable LBA must precede the backup partition
+    /// entry array.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Error::DiskSizeMismatch` if the backup header is not located in
+    /// the last sector of the disk and `Error::InvalidPartitionBoundaries` if the last usable LBA
+    /// overlaps the backup partition entry array.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// gpt.validate_against_disk(&mut f).expect("the disk has been resized");
+    ///
+    /// let mut bigger_disk = std::io::Cursor::new(vec![0; 200 * 512]);
+    /// assert!(matches!(
+    ///     gpt.validate_against_disk(&mut bigger_disk),
+    ///     Err(gptman::Error::DiskSizeMismatch { expected: 100, found: 200 })
+    /// ));
+    /// ```
+    pub fn validate_against_disk<S>(&self, seeker: &mut S) -> Result<()>
+    where
+        S: Seek + ?Sized,
+    {
+        let len = seeker.seek(SeekFrom::End(0))? / self.sector_size;
+        if self.disk_len_sectors() != len {
+            return Err(Error::DiskSizeMismatch {
+                expected: self.disk_len_sectors(),
+                found: len,
+            });
+        }
+
+        if self.header.last_usable_lba >= self.backup_partition_entry_lba() {
+            return Err(Error::InvalidPartitionBoundaries);
+        }
+
+        Ok(())
+    }
+
+    /// Get how the bytes of a disk of `disk_len` bytes are used: by the metadata of the GPT at
+    /// the beginning and at the end of the disk, by the partitions, or not at all.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Error::DiskTooSmall` if the usable sectors don't fit in the disk
+    /// and an error if a partition is invalid or doesn't fit in the usable sectors.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// let usage = gpt.disk_usage_breakdown(100 * 512).unwrap();
+    ///
+    /// assert_eq!(usage.primary_metadata, 34 * 512);
+    /// assert_eq!(usage.allocated, (10 + 5) * 512);
+    /// assert_eq!(usage.free, (33 - 10 - 5) * 512);
+    /// assert_eq!(usage.backup_metadata, 33 * 512);
+    /// ```
+    pub fn disk_usage_breakdown(&self, disk_len: u64) -> Result<DiskUsage> {
+        self.check_partition_boundaries()?;
+
+        let primary_metadata = self.first_usable_byte()?;
+        let usable = self.usable_byte_len()?;
+        let allocated =
+            self.partitions
+                .iter()
+                .filter(|x| x.is_used())
+                .try_fold(0_u64, |acc, x| {
+                    acc.checked_add(x.size_bytes(self.sector_size)?)
+                        .ok_or(Error::Overflow)
+                })?;
+        let required = self
+            .last_usable_byte()?
+            .checked_add(1)
+            .ok_or(Error::Overflow)?;
+        let backup_metadata = disk_len.checked_sub(required).ok_or(Error::DiskTooSmall {
+            required,
+            found: disk_len,
+        })?;
+
+        Ok(DiskUsage {
+            primary_metadata,
+            free: usable - allocated,
+            allocated,
+            backup_metadata,
+        })
+    }
+
+    /// Get the number of sectors occupied by a partition entry array (each copy of the GPT has
+    /// its own partition entry array).
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let ss = 512;
+    /// let data = vec![0; 100 * ss as usize];
+    /// let mut cur = std::io::Cursor::new(data);
+    /// let gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    ///     .expect("could not create partition table");
+    ///
+    /// // 128 partition entries of 128 bytes each
+    /// assert_eq!(gpt.partition_array_sectors(), 32);
+    /// ```
+    pub fn partition_array_sectors(&self) -> u64 {
+        partition_array_sectors(
+            self.header.number_of_partition_entries,
+            self.header.size_of_partition_entry,
+            self.sector_size,
+        )
+    }
+
+    /// Get the position (in bytes) of the first usable byte of the disk.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the computation overflows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let ss = 512;
+    /// let data = vec![0; 100 * ss as usize];
+    /// let mut cur = std::io::Cursor::new(data);
+    /// let gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    ///     .expect("could not create partition table");
+    ///
+    /// assert_eq!(gpt.first_usable_byte().unwrap(), gpt.header.first_usable_lba * 512);
+    /// ```
+    pub fn first_usable_byte(&self) -> Result<u64> {
+        self.header
+            .first_usable_lba
+            .checked_mul(self.sector_size)
+            .ok_or(Error::Overflow)
+    }
+
+    /// Get the position (in bytes) of the last usable byte of the disk. (Like `last_usable_lba`,
+    /// this position is inclusive.)
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the computation overflows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let ss = 512;
+    /// let data = vec![0; 100 * ss as usize];
+    /// let mut cur = std::io::Cursor::new(data);
+    /// let gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    ///     .expect("could not create partition table");
+    ///
+    /// assert_eq!(
+    ///     gpt.last_usable_byte().unwrap(),
+    ///     (gpt.header.last_usable_lba + 1) * 512 - 1
+    /// );
+    /// ```
+    pub fn last_usable_byte(&self) -> Result<u64> {
+        self.header
+            .last_usable_lba
+            .checked_add(1)
+            .and_then(|v| v.checked_mul(self.sector_size))
+            .map(|v| v - 1)
+            .ok_or(Error::Overflow)
+    }
+
+    /// Get the number of usable bytes on the disk (between the first usable byte and the last
+    /// usable byte included).
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the computation overflows or if the
+    /// `last_usable_lba` is lesser than the `first_usable_lba`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let ss = 512;
+    /// let data = vec![0; 100 * ss as usize];
+    /// let mut cur = std::io::Cursor::new(data);
+    /// let gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    ///     .expect("could not create partition table");
+    ///
+    /// assert_eq!(
+    ///     gpt.usable_byte_len().unwrap(),
+    ///     (gpt.header.last_usable_lba + 1 - gpt.header.first_usable_lba) * 512
+    /// );
+    /// ```
+    pub fn usable_byte_len(&self) -> Result<u64> {
+        let first = self.first_usable_byte()?;
+        let last = self.last_usable_byte()?;
+        if last < first {
+            return Err(Error::InvalidPartitionBoundaries);
+        }
+
+        Ok(last - first + 1)
+    }
+
+    /// Location (in sectors) of the partition entry array of the other copy of the header (the
+    /// backup copy if `self` is using the primary header and vice versa).
+    fn alternate_partition_entry_lba(&self) -> u64 {
+        if self.header.is_primary() {
+            self.header
+                .backup_lba
+                .saturating_sub(self.partition_array_sectors())
+        } else {
+            self.header.backup_lba + 1
+        }
+    }
+
+    fn entry_location_at(&self, partition_entry_lba: u64, i: u32) -> Result<u64> {
+        self.check_partition_number(i)?;
+
+        partition_entry_lba
+            .checked_mul(self.sector_size)
+            .and_then(|v| {
+                v.checked_add(u64::from(i - 1) * u64::from(self.header.size_of_partition_entry))
+            })
+            .ok_or(Error::Overflow)
+    }
+
+    /// Get the position (in bytes) of the primary header.
+    ///
+    /// See also: [`Self::backup_header_byte_offset`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the computation overflows.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// assert_eq!(gpt.primary_header_byte_offset().unwrap(), 512);
+    /// assert_eq!(gpt.backup_header_byte_offset().unwrap(), 99 * 512);
+    /// ```
+    pub fn primary_header_byte_offset(&self) -> Result<u64> {
+        let lba = if self.header.is_primary() {
+            self.header.primary_lba
+        } else {
+            self.header.backup_lba
+        };
+
+        lba.checked_mul(self.sector_size).ok_or(Error::Overflow)
+    }
+
+    /// Get the position (in bytes) of the backup header.
+    ///
+    /// See also: [`Self::primary_header_byte_offset`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the computation overflows.
+    pub fn backup_header_byte_offset(&self) -> Result<u64> {
+        let lba = if self.header.is_primary() {
+            self.header.backup_lba
+        } else {
+            self.header.primary_lba
+        };
+
+        lba.checked_mul(self.sector_size).ok_or(Error::Overflow)
+    }
+
+    /// Get the position (in bytes) of a partition entry in the primary partition entry array.
+    ///
+    /// This allows modifying a single partition entry on disk without rewriting the whole array.
+    /// (Note that the CRC32 checksums of the headers must be updated afterwards.)
+    ///
+    /// See also: [`Self::backup_entry_location`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `i` is lesser or equal to `0` or greater than the
+    /// number of partition entries, or if the computation overflows.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// assert_eq!(gpt.entry_location(1).unwrap(), 2 * 512);
+    /// assert_eq!(gpt.entry_location(2).unwrap(), 2 * 512 + 128);
+    /// ```
+    pub fn entry_location(&self, i: u32) -> Result<u64> {
+        self.entry_location_at(self.primary_partition_entry_lba(), i)
+    }
+
+    /// Get the position (in bytes) of a partition entry in the backup partition entry array.
+    ///
+    /// See also: [`Self::entry_location`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `i` is lesser or equal to `0` or greater than the
+    /// number of partition entries, or if the computation overflows.
+    pub fn backup_entry_location(&self, i: u32) -> Result<u64> {
+        self.entry_location_at(self.backup_partition_entry_lba(), i)
+    }
+
+    /// Zero the sectors of the previous partition entry arrays that are not used by a partition
+    /// nor by the current partition entry arrays (which can be wrote before or after).
+    fn zero_stale_partition_arrays<W>(&mut self, writer: &mut W) -> Result<()>
+    where
+        W: Write + Seek + ?Sized,
+    {
+        let sectors = self.partition_array_sectors();
+        let arrays = [
+            self.primary_partition_entry_lba(),
+            self.backup_partition_entry_lba(),
+        ];
+        let zeroes = vec![0; self.sector_size as usize];
+        for range in self.stale_partition_arrays.iter() {
+            for lba in range.clone() {
+                if self.find_at_sector(lba).is_some()
+                    || arrays.iter().any(|x| (*x..*x + sectors).contains(&lba))
+                {
+                    continue;
+                }
+                writer.seek(SeekFrom::Start(lba * self.sector_size))?;
+                writer.write_all(&zeroes)?;
+            }
+        }
+        self.stale_partition_arrays.clear();
+
+        Ok(())
+    }
+
+    /// Location (in sectors) of the primary partition entry array.
+    fn primary_partition_entry_lba(&self) -> u64 {
+        if self.header.is_primary() {
+            self.header.partition_entry_lba
+        } else {
+            self.alternate_partition_entry_lba()
+        }
+    }
+
+    /// Location (in sectors) of the backup partition entry array.
+    fn backup_partition_entry_lba(&self) -> u64 {
+        if self.header.is_backup() {
+            self.header.partition_entry_lba
+        } else {
+            self.alternate_partition_entry_lba()
+        }
+    }
+
+    /// Sort the partition entries in the array by the starting LBA.
+    pub fn sort(&mut self) {
+        self.partitions.sort_by(cmp_starting_lba);
+    }
+
+    /// Move all the used partition entries to the first slots of the array (ordered by starting
+    /// LBA) and reset all the other partition entries. Some firmwares don't support gaps in the
+    /// partition entry array.
+    ///
+    /// Note that the partition numbers change (e.g. `/dev/sda5` may become `/dev/sda1`).
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let mut gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// gpt[5] = gpt[1].clone();
+    /// gpt.remove(1).expect("could not remove partition");
+    ///
+    /// gpt.normalize();
+    ///
+    /// assert_eq!(gpt[1].partition_name.as_str(), "Foo");
+    /// assert_eq!(gpt[2].partition_name.as_str(), "Bar");
+    /// assert!(gpt[5].is_unused());
+    /// ```
+    pub fn normalize(&mut self) {
+        self.sort();
+        for partition in self.partitions.iter_mut().filter(|x| x.is_unused()) {
+            *partition = GPTPartitionEntry::empty();
+        }
+    }
+
+    /// Get the partition numbers ordered by the starting LBA of the partitions (the unused
+    /// partition entries come last). Unlike [`Self::sort`], the partition entries are not
+    /// renumbered.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let mut gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// let foo = gpt[1].clone();
+    /// gpt[1] = gpt[2].clone();
+    /// gpt[2] = foo;
+    ///
+    /// assert_eq!(gpt.sorted_indices()[..3], [2, 1, 3]);
+    /// assert_eq!(gpt[1].partition_name.as_str(), "Bar");
+    /// ```
+    pub fn sorted_indices(&self) -> Vec<u32> {
+        let mut indices: Vec<u32> = (1..=self.header.number_of_partition_entries).collect();
+        indices.sort_by(|a, b| cmp_starting_lba(&self[*a], &self[*b]));
+        indices
+    }
+
+    /// Remove a partition entry in the array.
+    ///
+    /// This is the equivalent of:
+    /// `gpt[i] = gptman::GPTPartitionEntry::empty();`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `i` is lesser or equal to `0` or greater than the
+    /// number of partition entries (which can be obtained in the header).
+    pub fn remove(&mut self, i: u32) -> Result<()> {
+        self.check_partition_number(i)?;
+
+        self.partitions[i as usize - 1] = GPTPartitionEntry::empty();
+
+        Ok(())
+    }
+
+    /// Replace a partition entry after checking that it can be wrote to disk. Unlike
+    /// `gpt[i] = entry`, the errors are returned immediately instead of when writing the GPT.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `i` is lesser or equal to `0` or greater than the
+    /// number of partition entries. If the entry is used, it will also return an error if the
+    /// partition doesn't fit in the usable sectors of the disk, if it overlaps another partition
+    /// or if its unique partition GUID is already used by another partition. Nothing is changed
+    /// in that case.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let mut gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// let entry = gptman::GPTPartitionEntry {
+    ///     partition_type_guid: [0xff; 16],
+    ///     unique_partition_guid: [0xee; 16],
+    ///     starting_lba: 54,
+    ///     ending_lba: 60,
+    ///     attribute_bits: 0,
+    ///     partition_name: "Baz".into(),
+    /// };
+    ///
+    /// assert!(gpt
+    ///     .set_partition(3, gptman::GPTPartitionEntry { starting_lba: 50, ..entry.clone() })
+    ///     .is_err());
+    /// gpt.set_partition(3, entry).expect("could not set partition");
+    ///
+    /// assert_eq!(gpt[3].partition_name.as_str(), "Baz");
+    /// ```
+    pub fn set_partition(&mut self, i: u32, entry: GPTPartitionEntry) -> Result<()> {
+        self.check_partition_number(i)?;
+        if entry.is_used() {
+            self.check_partition_placement(i, entry.starting_lba, entry.ending_lba)?;
+            self.check_unique_partition_guid(i, &entry.unique_partition_guid)?;
+        }
+
+        self.partitions[i as usize - 1] = entry;
+
+        Ok(())
+    }
+
+    /// Parse a partition entry specification: a comma separated list of `key=value`, like
+    /// `type=EFI System partition,start=2048,size=1GiB,name=EFI,attr=2`. The entry is not placed
+    /// in the partition table, this is up to the caller (see [`Self::set_partition`]).
+    ///
+    /// The keys are:
+    ///
+    ///  *  `type` (mandatory): the partition type GUID, or the name or an alias of a well-known
+    ///     partition type (see [`partition_types::find_by_name`] and
+    ///     [`partition_types::find_by_alias`]).
+    ///  *  `start`: the starting LBA of the partition. By default, the partition starts at the
+    ///     first aligned free spot (see [`Self::find_first_place`]).
+    ///  *  `size`: the size of the partition. By default, the partition extends to the end of the
+    ///     free spot where it starts.
+    ///  *  `name`: the name of the partition. It can't contain a comma and the whitespace around it
+    ///     is trimmed like for every other value.
+    ///  *  `attr`: the attribute bits of the partition, in decimal or in hexadecimal (prefixed by
+    ///     `0x`).
+    ///  *  `uuid`: the unique partition GUID. By default, it is left to zero: the caller is
+    ///     expected to generate one.
+    ///
+    /// The start and the size are in sectors but they can also be followed by a unit (`B`, `KiB`,
+    /// `MiB`, `GiB` or `TiB`), in which case they are converted using the sector size of the GPT and
+    /// rounded up to whole sectors.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Error::InvalidEntrySpec` if the specification can't be parsed,
+    /// an error if the partition type is unknown or the name is invalid, and `Error::NoSpaceLeft`
+    /// if no free spot has been found for the partition.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let mut gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// let mut entry = gpt.parse_entry_spec("type=linux,size=4KiB,name=Baz")
+    ///     .expect("invalid specification");
+    /// entry.unique_partition_guid = [0xee; 16];
+    ///
+    /// assert_eq!(entry.starting_lba, 54);
+    /// assert_eq!(entry.ending_lba, 61);
+    /// gpt.set_partition(3, entry).expect("could not set partition");
+    /// ```
+    pub fn parse_entry_spec(&self, spec: &str) -> Result<GPTPartitionEntry> {
+        let invalid = |reason: &str| Error::InvalidEntrySpec(format!("{}: {}", reason, spec));
+
+        let mut entry = GPTPartitionEntry::empty();
+        let mut partition_type = None;
+        let mut start = None;
+        let mut size = None;
+
+        for field in spec.split(',') {
+            let (key, value) = field
+                .split_once('=')
+                .ok_or_else(|| invalid("expected key=value"))?;
+            let value = value.trim();
+            match key.trim() {
+                "type" => {
+                    partition_type = Some(match parse_guid(value) {
+                        Ok(guid) => guid,
+                        Err(_) => match partition_types::find_by_alias(value) {
+                            Some(x) => x.guid,
+                            None => partition_types::find_by_name(value)?.guid,
+                        },
+                    });
+                }
+                "start" => {
+                    start = Some(
+                        parse_sectors(value, self.sector_size)
+                            .ok_or_else(|| invalid("invalid start"))?,
+                    );
+                }
+                "size" => {
+                    size = Some(
+                        parse_sectors(value, self.sector_size)
+                            .filter(|x| *x > 0)
+                            .ok_or_else(|| invalid("invalid size"))?,
+                    );
+                }
+                "name" => entry.partition_name = PartitionName::new(value)?,
+                "attr" => {
+                    entry.attribute_bits = match value.strip_prefix("0x") {
+                        Some(hex) => u64::from_str_radix(hex, 16),
+                        None => value.parse(),
+                    }
+                    .map_err(|_| invalid("invalid attributes"))?;
+                }
+                "uuid" => entry.unique_partition_guid = parse_guid(value)?,
+                _ => return Err(invalid("unknown key")),
+            }
+        }
+
+        entry.partition_type_guid = partition_type.ok_or_else(|| invalid("missing type"))?;
+        entry.starting_lba = match start {
+            Some(x) => x,
+            None => self
+                .find_first_place(size.unwrap_or(1))
+                .ok_or(Error::NoSpaceLeft)?,
+        };
+        let size = match size {
+            Some(x) => x,
+            None => self
+                .find_free_sectors()
+                .iter()
+                .find(|(i, l)| (*i..*i + *l).contains(&entry.starting_lba))
+                .map(|(i, l)| i + l - entry.starting_lba)
+                .ok_or(Error::NoSpaceLeft)?,
+        };
+        entry.ending_lba = entry
+            .starting_lba
+            .checked_add(size - 1)
+            .ok_or(Error::Overflow)?;
+
+        Ok(entry)
+    }
+
+    /// Get the name of a partition entry.
+    ///
+    /// Unlike `gpt[i]`, this function doesn't panic if `i` is not a valid partition number and
+    /// returns an owned value (which is convenient for language bindings).
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `i` is lesser or equal to `0` or greater than the
+    /// number of partition entries.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// assert_eq!(gpt.partition_name(1).unwrap(), "Foo");
+    /// assert_eq!(gpt.partition_type(1).unwrap(), gpt[1].partition_type_guid);
+    /// assert_eq!(gpt.unique_guid(1).unwrap(), gpt[1].unique_partition_guid);
+    /// assert!(gpt.partition_name(0).is_err());
+    /// ```
+    pub fn partition_name(&self, i: u32) -> Result<String> {
+        self.check_partition_number(i)?;
+
+        Ok(self[i].partition_name.as_str().to_string())
+    }
+
+    /// Get the partition type GUID of a partition entry.
+    ///
+    /// See also: [`Self::partition_name`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `i` is lesser or equal to `0` or greater than the
+    /// number of partition entries.
+    pub fn partition_type(&self, i: u32) -> Result<[u8; 16]> {
+        self.check_partition_number(i)?;
+
+        Ok(self[i].partition_type_guid)
+    }
+
+    /// Get the unique partition GUID of a partition entry.
+    ///
+    /// See also: [`Self::partition_name`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `i` is lesser or equal to `0` or greater than the
+    /// number of partition entries.
+    pub fn unique_guid(&self, i: u32) -> Result<[u8; 16]> {
+        self.check_partition_number(i)?;
+
+        Ok(self[i].unique_partition_guid)
+    }
+
+    /// Set the name of a partition entry.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `i` is lesser or equal to `0` or greater than the
+    /// number of partition entries, or if the name is not valid (see [`PartitionName::new`]).
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let mut gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// gpt.set_partition_name(1, "A Robot Named Fight!")
+    ///     .expect("could not set partition name");
+    /// assert!(gpt.set_partition_name(1, &"x".repeat(37)).is_err());
+    /// assert!(gpt.set_partition_name(0, "Foo").is_err());
+    ///
+    /// assert_eq!(gpt[1].partition_name.as_str(), "A Robot Named Fight!");
+    /// ```
+    pub fn set_partition_name(&mut self, i: u32, name: &str) -> Result<()> {
+        self.check_partition_number(i)?;
+
+        self.partitions[i as usize - 1].partition_name = PartitionName::new(name)?;
+
+        Ok(())
+    }
+
+    /// Set the type GUID of a partition entry.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `i` is lesser or equal to `0` or greater than the
+    /// number of partition entries.
+    pub fn set_partition_type_guid(&mut self, i: u32, partition_type_guid: [u8; 16]) -> Result<()> {
+        self.check_partition_number(i)?;
+
+        self.partitions[i as usize - 1].partition_type_guid = partition_type_guid;
+
+        Ok(())
+    }
+
+    /// Set the unique GUID of a partition entry.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `i` is lesser or equal to `0` or greater than the
+    /// number of partition entries, or if the GUID is already used by another partition.
+    pub fn set_unique_partition_guid(
+        &mut self,
+        i: u32,
+        unique_partition_guid: [u8; 16],
+    ) -> Result<()> {
+        self.check_partition_number(i)?;
+        self.check_unique_partition_guid(i, &unique_partition_guid)?;
+
+        self.partitions[i as usize - 1].unique_partition_guid = unique_partition_guid;
+
+        Ok(())
+    }
+
+    /// Set the attribute bits of a partition entry.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `i` is lesser or equal to `0` or greater than the
+    /// number of partition entries.
+    pub fn set_attribute_bits(&mut self, i: u32, attribute_bits: u64) -> Result<()> {
+        self.check_partition_number(i)?;
+
+        self.partitions[i as usize - 1].attribute_bits = attribute_bits;
+
+        Ok(())
+    }
+
+    /// Set the type of a partition entry using the name of a well-known partition type (case
+    /// insensitive).
+    ///
+    /// See [`partition_types::PARTITION_TYPES`] for the list of the partition types available.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `i` is lesser or equal to `0` or greater than the
+    /// number of partition entries, if the name doesn't match any known partition type, or if the
+    /// name matches more than one partition type.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let mut gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// gpt.set_partition_type_by_name(1, "EFI System partition")
+    ///     .expect("could not set partition type");
+    ///
+    /// assert_eq!(
+    ///     gpt[1].partition_type_guid,
+    ///     [0x28, 0x73, 0x2a, 0xc1, 0x1f, 0xf8, 0xd2, 0x11, 0xba, 0x4b, 0x00, 0xa0, 0xc9, 0x3e, 0xc9, 0x3b]
+    /// );
+    /// ```
+    pub fn set_partition_type_by_name(&mut self, i: u32, name: &str) -> Result<()> {
+        self.check_partition_number(i)?;
+
+        self.partitions[i as usize - 1].partition_type_guid =
+            partition_types::find_by_name(name)?.guid;
+
+        Ok(())
+    }
+
+    /// Copy the partition entry `src` to the partition entry `dst` in the same array, moving it to
+    /// start at `new_start` and giving it the unique GUID `unique_partition_guid`.
+    ///
+    /// The type, the name, the attribute bits and the size of the partition are kept. If `dst` is
+    /// already used, it will be overwritten.
+    ///
+    /// Note that this only changes the partition table: the data of the partition is not copied.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `src` or `dst` is an invalid partition number, if
+    /// `src` is unused, if the new partition would not fit within the disk or would overlap
+    /// another partition, or if `unique_partition_guid` is already used by another partition.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let mut gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    /// gpt.align = 1;
+    ///
+    /// let size = gpt[2].size().unwrap();
+    /// let new_start = gpt.find_first_place(size).expect("no space left");
+    /// gpt.clone_partition_within(2, 3, new_start, [0xff; 16])
+    ///     .expect("could not clone partition");
+    ///
+    /// assert_eq!(gpt[3].partition_name, gpt[2].partition_name);
+    /// assert_eq!(gpt[3].size().unwrap(), size);
+    /// ```
+    pub fn clone_partition_within(
+        &mut self,
+        src: u32,
+        dst: u32,
+        new_start: u64,
+        unique_partition_guid: [u8; 16],
+    ) -> Result<()> {
+        self.check_partition_number(src)?;
+        self.check_partition_number(dst)?;
+        if self[src].is_unused() {
+            return Err(Error::UnusedPartition);
+        }
+
+        let ending_lba = new_start
+            .checked_add(self[src].size()? - 1)
+            .ok_or(Error::Overflow)?;
+        self.check_partition_placement(dst, new_start, ending_lba)?;
+        self.check_unique_partition_guid(dst, &unique_partition_guid)?;
+
+        self[dst] = GPTPartitionEntry {
+            unique_partition_guid,
+            starting_lba: new_start,
+            ending_lba,
+            ..self[src].clone()
+        };
+
+        Ok(())
+    }
+
+    /// Remove a partition entry in the array that resides at a given sector.
+    ///
+    /// # Errors
+    ///
+    /// It is an error to provide a sector which does not belong to a partition.
+    pub fn remove_at_sector(&mut self, sector: u64) -> Result<()> {
+        self.remove(
+            self.find_at_sector(sector)
+                .ok_or(Error::PartitionNotFound)?,
+        )
+    }
+
+    /// Remove all the partition entries in the array. The header (and the disk GUID) are kept
+    /// unchanged.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let mut gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// gpt.clear();
+    ///
+    /// assert!(gpt.iter().all(|(_, p)| p.is_unused()));
+    /// ```
+    pub fn clear(&mut self) {
+        for partition in self.partitions.iter_mut() {
+            *partition = GPTPartitionEntry::empty();
+        }
+    }
+
+    /// Change the number of partition entries in the array. The `first_usable_lba` and the
+    /// `last_usable_lba` of the header are updated to match the new size of the partition entry
+    /// arrays, unless they have been moved away from the arrays to reserve some sectors (see
+    /// [`Self::set_first_usable_lba`] and [`Self::set_last_usable_lba`]): they are kept in that
+    /// case.
+    ///
+    /// When the number of partition entries is reduced, the sectors of the previous partition
+    /// entry arrays that are not used anymore (and not used by a partition) are zeroed on the next
+    /// write so no stale partition entry remains on the disk.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Error::InvalidNumberOfPartitionEntries` if `n` is `0` or greater
+    /// than 2^20 (or if the partition entry array would be bigger than 128 MiB),
+    /// `Error::UsedPartitionEntryDropped` if a used partition entry would be dropped from the
+    /// array, and `Error::InvalidPartitionBoundaries` if the partition entry arrays would overlap
+    /// a used partition or the usable sectors. Nothing is changed in that case.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let ss = 512;
+    /// let data = vec![0; 100 * ss as usize];
+    /// let mut cur = std::io::Cursor::new(data);
+    /// let mut gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    ///     .expect("could not create partition table");
+    ///
+    /// gpt.set_number_of_partition_entries(4)
+    ///     .expect("could not change the number of partition entries");
+    ///
+    /// assert_eq!(gpt.header.number_of_partition_entries, 4);
+    /// assert_eq!(gpt.header.first_usable_lba, 3);
+    /// assert_eq!(gpt.header.last_usable_lba, 97);
+    /// ```
+    pub fn set_number_of_partition_entries(&mut self, n: u32) -> Result<()> {
+        if n == 0
+            || n > MAX_PARTITION_ENTRIES
+            || u64::from(n) * u64::from(self.header.size_of_partition_entry)
+                > MAX_PARTITION_ENTRY_ARRAY_SIZE
+        {
+            return Err(Error::InvalidNumberOfPartitionEntries(n));
+        }
+        if let Some((i, _)) = self.iter().skip(n as usize).find(|(_, x)| x.is_used()) {
+            return Err(Error::UsedPartitionEntryDropped(i));
+        }
+
+        let partition_array_size =
+            partition_array_sectors(n, self.header.size_of_partition_entry, self.sector_size);
+        let primary_partition_entry_lba = self.primary_partition_entry_lba();
+        let backup_lba = if self.header.is_primary() {
+            self.header.backup_lba
+        } else {
+            self.header.primary_lba
+        };
+        let backup_partition_entry_lba = backup_lba
+            .checked_sub(partition_array_size)
+            .ok_or(Error::InvalidPartitionBoundaries)?;
+        // NOTE: the usable LBAs are only moved if they are next to the partition entry arrays,
+        //       a reserved area (see set_first_usable_lba and set_last_usable_lba) is kept
+        let first_usable_lba = if self.header.first_usable_lba
+            == primary_partition_entry_lba + self.partition_array_sectors()
+        {
+            primary_partition_entry_lba + partition_array_size
+        } else {
+            self.header.first_usable_lba
+        };
+        let last_usable_lba =
+            if self.header.last_usable_lba + 1 == self.backup_partition_entry_lba() {
+                backup_partition_entry_lba
+                    .checked_sub(1)
+                    .ok_or(Error::InvalidPartitionBoundaries)?
+            } else {
+                self.header.last_usable_lba
+            };
+        if first_usable_lba < primary_partition_entry_lba + partition_array_size
+            || last_usable_lba >= backup_partition_entry_lba
+            || last_usable_lba < first_usable_lba
+            || self
+                .partitions
+                .iter()
+                .filter(|x| x.is_used())
+                .any(|x| x.starting_lba < first_usable_lba || x.ending_lba > last_usable_lba)
+        {
+            return Err(Error::InvalidPartitionBoundaries);
+        }
+
+        if n < self.header.number_of_partition_entries {
+            let partition_array_sectors = self.partition_array_sectors();
+            for lba in [
+                self.primary_partition_entry_lba(),
+                self.backup_partition_entry_lba(),
+            ] {
+                self.stale_partition_arrays
+                    .push(lba..=lba + partition_array_sectors - 1);
+            }
+        }
+
+        self.partitions
+            .resize(n as usize, GPTPartitionEntry::empty());
+        self.header.number_of_partition_entries = n;
+        self.header.first_usable_lba = first_usable_lba;
+        self.header.last_usable_lba = last_usable_lba;
+        if self.header.is_backup() {
+            self.header.partition_entry_lba = backup_partition_entry_lba;
+        }
+
+        Ok(())
+    }
+
+    /// Reduce the number of partition entries to the minimum that keeps every used partition
+    /// entry, rounded up to fill whole sectors. The usable sectors are extended accordingly unless
+    /// some sectors are reserved before or after them (see
+    /// [`Self::set_number_of_partition_entries`]). Returns the new number of partition entries.
+    ///
+    /// Note that the UEFI specification requires at least 16384 bytes (128 entries of 128 bytes)
+    /// to be reserved for the partition entry array, so some firmwares might reject the resulting
+    /// partition table. This is intended for size constrained media.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the partition entry arrays would overlap a used
+    /// partition. Nothing is changed in that case.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let mut gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// assert_eq!(gpt.trim_unused_trailing_entries().unwrap(), 4);
+    /// assert_eq!(gpt.header.number_of_partition_entries, 4);
+    /// assert_eq!(gpt.header.first_usable_lba, 3);
+    /// ```
+    pub fn trim_unused_trailing_entries(&mut self) -> Result<u32> {
+        let last_used = self
+            .iter()
+            .filter(|(_, x)| x.is_used())
+            .map(|(i, _)| i)
+            .last()
+            .unwrap_or(1);
+        let entries_per_sector =
+            (self.sector_size / u64::from(self.header.size_of_partition_entry)).max(1) as u32;
+        let n = ((last_used - 1) / entries_per_sector + 1)
+            .saturating_mul(entries_per_sector)
+            .min(self.header.number_of_partition_entries);
+
+        if n != self.header.number_of_partition_entries {
+            self.set_number_of_partition_entries(n)?;
+        }
+
+        Ok(n)
+    }
+
+    /// Change the last usable LBA of the disk. This can be used to leave some space at the end of
+    /// the disk that is not managed by the partition table. The backup header and its partition
+    /// entry array stay at the end of the disk.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Error::InvalidPartitionBoundaries` if `lba` is lesser than the
+    /// first usable LBA minus one, if it overlaps the backup partition entry array or if a used
+    /// partition extends past it. Nothing is changed in that case.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let ss = 512;
+    /// let data = vec![0; 100 * ss as usize];
+    /// let mut cur = std::io::Cursor::new(data);
+    /// let mut gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    ///     .expect("could not create partition table");
+    ///
+    /// gpt.set_last_usable_lba(50).expect("could not set the last usable LBA");
+    /// assert_eq!(gpt.header.last_usable_lba, 50);
+    /// assert_eq!(gpt.header.backup_lba, 99);
+    ///
+    /// assert!(gpt.set_last_usable_lba(98).is_err());
+    /// ```
+    pub fn set_last_usable_lba(&mut self, lba: u64) -> Result<()> {
+        if lba < self.header.first_usable_lba.saturating_sub(1)
+            || lba >= self.backup_partition_entry_lba()
+            || self
+                .partitions
+                .iter()
+                .any(|x| x.is_used() && x.ending_lba > lba)
+        {
+            return Err(Error::InvalidPartitionBoundaries);
+        }
+
+        self.header.last_usable_lba = lba;
+
+        Ok(())
+    }
+
+    /// Change the first usable LBA of the disk. This can be used to leave some space after the
+    /// primary partition entry array that is not managed by the partition table (e.g. for a
+    /// bootloader).
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Error::InvalidPartitionBoundaries` if `lba` overlaps the primary
+    /// partition entry array, if it is greater than the last usable LBA plus one or if a used
+    /// partition starts before it. Nothing is changed in that case.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let ss = 512;
+    /// let data = vec![0; 100 * ss as usize];
+    /// let mut cur = std::io::Cursor::new(data);
+    /// let mut gpt = gptman::GPT::new_from(&mut cur, ss as u64, [0xff; 16])
+    ///     .expect("could not create partition table");
+    ///
+    /// gpt.set_first_usable_lba(40).expect("could not set the first usable LBA");
+    /// assert_eq!(gpt.header().first_usable_lba, 40);
+    ///
+    /// assert!(gpt.set_first_usable_lba(33).is_err());
+    /// ```
+    pub fn set_first_usable_lba(&mut self, lba: u64) -> Result<()> {
+        let end_of_partition_array = self
+            .primary_partition_entry_lba()
+            .checked_add(self.partition_array_sectors())
+            .ok_or(Error::Overflow)?;
+        if lba < end_of_partition_array
+            || lba > self.header.last_usable_lba.saturating_add(1)
+            || self
+                .partitions
+                .iter()
+                .any(|x| x.is_used() && x.starting_lba < lba)
+        {
+            return Err(Error::InvalidPartitionBoundaries);
+        }
+
+        self.header.first_usable_lba = lba;
+
+        Ok(())
+    }
+
+    /// Find the first partition entry of the array that is not used.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Error::NoFreeSlot` if all the partition entries are used. (Not
+    /// to be confused with `Error::NoSpaceLeft` which is returned when there is no space left on
+    /// the disk.)
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// assert_eq!(gpt.find_free_partition_number().unwrap(), 3);
+    /// ```
+    pub fn find_free_partition_number(&self) -> Result<u32> {
+        self.iter()
+            .find(|(_, x)| x.is_unused())
+            .map(|(i, _)| i)
+            .ok_or(Error::NoFreeSlot)
+    }
+
+    /// Get the number of partition entries that are used in the array.
+    ///
+    /// The total number of partition entries can be obtained in the header
+    /// (`number_of_partition_entries`).
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// println!(
+    ///     "Partitions: {} used / {} slots",
+    ///     gpt.used_partition_count(),
+    ///     gpt.header.number_of_partition_entries
+    /// );
+    /// assert_eq!(gpt.used_partition_count(), 2);
+    /// ```
+    pub fn used_partition_count(&self) -> u32 {
+        self.partitions.iter().filter(|x| x.is_used()).count() as u32
+    }
+
+    /// Get all the partition entries of the array (including the unused ones).
+    ///
+    /// Note that the slice starts at index 0 while the partition numbers start at 1.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// assert_eq!(gpt.partitions().len(), 128);
+    /// assert_eq!(gpt.partitions()[0], gpt[1]);
+    /// ```
+    pub fn partitions(&self) -> &[GPTPartitionEntry] {
+        &self.partitions
+    }
+
+    /// Replace the disk GUID and the unique partition GUIDs of all the used partitions by GUIDs
+    /// obtained from `gen` (which is called once for the disk and once for every used partition).
+    /// This allows using any source of randomness without adding a dependency to this crate.
+    ///
+    /// Note that the GUIDs returned by `gen` must be unique, otherwise writing the GPT will fail
+    /// (see [`Self::verify`]).
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let mut gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// let mut counter = 0;
+    /// gpt.randomize_guids_with(|| {
+    ///     counter += 1;
+    ///     [counter; 16]
+    /// });
+    ///
+    /// assert_eq!(gpt.header.disk_guid, [1; 16]);
+    /// assert_eq!(gpt[1].unique_partition_guid, [2; 16]);
+    /// assert_eq!(gpt[2].unique_partition_guid, [3; 16]);
+    /// assert!(gpt.verify().is_ok());
+    /// ```
+    pub fn randomize_guids_with<F>(&mut self, mut gen: F)
+    where
+        F: FnMut() -> [u8; 16],
+    {
+        self.header.disk_guid = gen();
+        for partition in self.partitions.iter_mut().filter(|x| x.is_used()) {
+            partition.unique_partition_guid = gen();
+        }
+    }
+
+    /// Get the unique partition GUIDs of all the used partitions. This allows checking that a
+    /// GUID is not used yet before assigning it to a partition.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// let guids = gpt.used_unique_guids();
+    /// assert_eq!(guids.len(), 2);
+    /// assert!(guids.contains(&gpt[1].unique_partition_guid));
+    /// assert!(!guids.contains(&[0xff; 16]));
+    /// ```
+    pub fn used_unique_guids(&self) -> HashSet<[u8; 16]> {
+        self.partitions
+            .iter()
+            .filter(|x| x.is_used())
+            .map(|x| x.unique_partition_guid)
+            .collect()
+    }
+
+    /// Get the partition type GUIDs of all the used partitions.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// assert!(gpt.used_type_guids().contains(&gpt[1].partition_type_guid));
+    /// ```
+    pub fn used_type_guids(&self) -> HashSet<[u8; 16]> {
+        self.partitions
+            .iter()
+            .filter(|x| x.is_used())
+            .map(|x| x.partition_type_guid)
+            .collect()
+    }
+
+    /// Check if two GPTs describe the same layout: same sector size, same disk GUID, same usable
+    /// sectors and same used partitions (whatever their slots in the partition entry array are).
+    ///
+    /// Unlike `==`, the checksums, the number of partition entries, the locations of the headers
+    /// and `align` are not compared. This is useful to verify a clone of a disk.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+    ///     .expect("could not open disk");
+    /// let gpt = gptman::GPT::find_from(&mut f)
+    ///     .expect("could not find GPT");
+    ///
+    /// let mut other = gpt.clone();
+    /// other.normalize();
+    /// other.align = 1;
+    /// other.header.crc32_checksum = 0;
+    ///
+    /// assert!(gpt.equivalent_to(&other));
+    ///
+    /// other[1].partition_name = "Baz".into();
+    /// assert!(!gpt.equivalent_to(&other));
+    /// ```
+    pub fn equivalent_to(&self, other: &GPT) -> bool {
+        fn used(gpt: &GPT) -> Vec<&GPTPartitionEntry> {
+            let mut partitions: Vec<_> = gpt.partitions.iter().filter(|x| x.is_used()).collect();
+            partitions.sort_by_key(|x| (x.starting_lba, x.ending_lba, x.unique_partition_guid));
+            partitions
+        }
+
+        self.sector_size == other.sector_size
+            && self.header.disk_guid == other.header.disk_guid
+            && self.header.first_usable_lba == other.header.first_usable_lba
+            && self.header.last_usable_lba == other.header.last_usable_lba
+            && used(self) == used(other)
+    }
+
+    /// Get an iterator over the partition entries and their index. The index always starts at 1.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &GPTPartitionEntry)> {
+        self.partitions
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (i as u32 + 1, x))
+    }
+
+    /// Get a mutable iterator over the partition entries and their index. The index always starts
+    /// at 1.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (u32, &mut GPTPartitionEntry)> {
+        self.partitions
+            .iter_mut()
+            .enumerate()
+            .map(|(i, x)| (i as u32 + 1, x))
+    }
+
+    /// Write a protective MBR in the first sector of the disk (see
+    /// [`Self::write_protective_mbr_into`]) only if the disk doesn't have one yet. A disk has a
+    /// protective MBR if its first sector has the MBR signature and at least one partition of
+    /// type `0xee` starting at the LBA `1`, like the one built by [`Self::protective_mbr_bytes`].
+    ///
+    /// The size of the `0xee` partition is not checked: a hybrid MBR (where the `0xee` partition
+    /// only covers the GPT metadata) and the protective MBR of a disk that has been grown are
+    /// left untouched. Any other MBR (including its bootstrap code area) is overwritten.
+    ///
+    /// Returns `true` if a protective MBR has been wrote.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the sector size is invalid or if the first sector
+    /// of the disk can't be read.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let ss = 512;
+    /// let mut cur = std::io::Cursor::new(vec![0; 100 * ss as usize]);
+    ///
+    /// assert!(gptman::GPT::ensure_protective_mbr(&mut cur, ss).unwrap());
+    /// assert!(!gptman::GPT::ensure_protective_mbr(&mut cur, ss).unwrap());
+    /// ```
+    pub fn ensure_protective_mbr<D>(disk: &mut D, sector_size: u64) -> Result<bool>
+    where
+        D: Read + Write + Seek + ?Sized,
+    {
+        check_sector_size(sector_size)?;
+
+        let mut mbr = [0; 512];
+        disk.seek(SeekFrom::Start(0))?;
+        disk.read_exact(&mut mbr)?;
+
+        // NOTE: the boot indicator, the CHS addresses and the size are not checked
+        let is_protective = |i: usize| {
+            let entry = &mbr[446 + i * 16..446 + (i + 1) * 16];
+            entry[4] == 0xee && entry[8..12] == 1_u32.to_le_bytes()
+        };
+        if mbr[510..] == [0x55, 0xaa] && (0..4).any(is_protective) {
+            return Ok(false);
+        }
+
+        Self::write_protective_mbr_into(disk, sector_size)?;
+
+        Ok(true)
+    }
+
+    /// This function writes a protective MBR in the first sector of the disk
+    /// starting at byte 446 and ending at byte 511. Any existing data will be overwritten.
+    ///
+    /// See also: [`Self::write_bootable_protective_mbr_into`].
+    pub fn write_protective_mbr_into<W>(mut writer: &mut W, sector_size: u64) -> Result<()>
+    where
+        W: Write + Seek + ?Sized,
+    {
+        Self::write_protective_mbr_into_impl(&mut writer, sector_size, false)
+    }
+
+    /// This function writes a protective MBR in the first sector of the disk
+    /// starting at byte 446 and ending at byte 511. Any existing data will be overwritten.
+    /// This function differs from [`Self::write_protective_mbr_into`] in that the partition in the
+    /// MBR partition table is marked as bootable. Some legacy BIOS systems do not consider a disk
+    /// to be bootable if there isn't an MBR partition marked as bootable in the MBR partition
+    /// table.
+    ///
+    /// <div class="warning">Some systems will not consider a disk to be bootable in UEFI mode
+    /// if the pMBR is marked as bootable, so this should only be used if booting on legacy BIOS
+    /// systems is a requirement.</div>
+    pub fn write_bootable_protective_mbr_into<W>(mut writer: &mut W, sector_size: u64) -> Result<()>
+    where
+        W: Write + Seek + ?Sized,
+    {
+        Self::write_protective_mbr_into_impl(&mut writer, sector_size, true)
+    }
+
+    fn write_protective_mbr_into_impl<W>(
+        writer: &mut W,
+        sector_size: u64,
+        bootable: bool,
+    ) -> Result<()>
+    where
+        W: Write + Seek + ?Sized,
+    {
+        let disk_sectors = writer.seek(SeekFrom::End(0))? / sector_size;
+        let mbr = Self::protective_mbr_bytes_impl(disk_sectors, bootable);
+        writer.seek(SeekFrom::Start(446))?;
+        writer.write_all(&mbr[446..])?;
+
+        Ok(())
+    }
+
+    /// Returns the first 512 bytes of a disk of `disk_sectors` sectors containing a protective
+    /// MBR. The bytes `0` to `445` (bootstrap code area) are zeroed.
+    ///
+    /// This is what [`Self::write_protective_mbr_into`] writes (starting at byte 446) on the disk.
+    ///
+    /// See also: [`Self::bootable_protective_mbr_bytes`].
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    /// ```
+    /// let mbr = gptman::GPT::protective_mbr_bytes(100);
+    ///
+    /// assert_eq!(mbr[446 + 4], 0xee);
+    /// assert_eq!(&mbr[510..], &[0x55, 0xaa]);
+    /// ```
+    pub fn protective_mbr_bytes(disk_sectors: u64) -> [u8; 512] {
+        Self::protective_mbr_bytes_impl(disk_sectors, false)
+    }
+
+    /// Returns the first 512 bytes of a disk of `disk_sectors` sectors containing a protective
+    /// MBR with its partition marked as bootable. The bytes `0` to `445` (bootstrap code area) are
+    /// zeroed.
+    ///
+    /// This is what [`Self::write_bootable_protective_mbr_into`] writes (starting at byte 446) on
+    /// the disk.
+    pub fn bootable_protective_mbr_bytes(disk_sectors: u64) -> [u8; 512] {
+        Self::protective_mbr_bytes_impl(disk_sectors, true)
+    }
+
+    fn protective_mbr_bytes_impl(disk_sectors: u64, bootable: bool) -> [u8; 512] {
+        let size = disk_sectors.saturating_sub(1).min(u64::from(u32::MAX)) as u32;
+        let mut mbr = [0; 512];
+        // partition 1
+        mbr[446] = if bootable { 0x80 } else { 0x00 };
+        mbr[447..458].copy_from_slice(&[
+            0x00, 0x02, 0x00, // CHS address of first absolute sector
+            0xee, // partition type
+            0xff, 0xff, 0xff, // CHS address of last absolute sector
+            0x01, 0x00, 0x00, 0x00, // LBA of first absolute sector
+        ]);
+        // number of sectors in partition 1
+        mbr[458..462].copy_from_slice(&size.to_le_bytes());
+        // NOTE: partitions 2, 3 and 4 are left empty
+        mbr[510..].copy_from_slice(&[0x55, 0xaa]); // signature
+
+        mbr
+    }
+
+    /// Returns `true` if the `GPTHeader` is a primary copy (the header is located before its backup
+    /// copy, usually at the beginning of the disk).
+    pub fn is_primary(&self) -> bool {
+        self.header.is_primary()
+    }
+
+    /// Returns `true` if the `GPTHeader` is a backup copy (the header is located at the end of the
+    /// disk).
+    ///
+    /// Note that when the header is a backup copy, the `primary_lba` is the LBA of the backup copy
+    /// and the `backup_lba` is the LBA of the primary copy.
+    pub fn is_backup(&self) -> bool {
+        self.header.is_backup()
+    }
+}
+
+impl Index<u32> for GPT {
+    type Output = GPTPartitionEntry;
+
+    fn index(&self, i: u32) -> &GPTPartitionEntry {
+        assert!(i != 0, "invalid partition index: 0");
+        &self.partitions[i as usize - 1]
+    }
+}
+
+impl IndexMut<u32> for GPT {
+    fn index_mut(&mut self, i: u32) -> &mut GPTPartitionEntry {
+        assert!(i != 0, "invalid partition index: 0");
+        &mut self.partitions[i as usize - 1]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::disallowed_names)]
+
+    use super::*;
+    use std::fs;
+
+    const DISK1: &str = "tests/fixtures/disk1.img";
+    const DISK2: &str = "tests/fixtures/disk2.img";
+
+    #[test]
+    fn send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<GPT>();
+        assert_send_sync::<GPTHeader>();
+        assert_send_sync::<GPTPartitionEntry>();
+        assert_send_sync::<PartitionName>();
+        assert_send_sync::<Error>();
+    }
+
+    #[test]
+    fn read_header_and_partition_entries() {
+        fn test(path: &str, ss: u64) {
+            let mut f = fs::File::open(path).unwrap();
+
+            f.seek(SeekFrom::Start(ss)).unwrap();
+            let mut gpt = GPTHeader::read_from(&mut f).unwrap();
+
+            f.seek(SeekFrom::Start(gpt.backup_lba * ss)).unwrap();
+            assert!(GPTHeader::read_from(&mut f).is_ok());
+
+            f.seek(SeekFrom::Start(gpt.partition_entry_lba * ss))
+                .unwrap();
+            let foo = GPTPartitionEntry::read_from(&mut f).unwrap();
+            assert!(!foo.is_unused());
+
+            f.seek(SeekFrom::Start(
+                gpt.partition_entry_lba * ss + u64::from(gpt.size_of_partition_entry),
+            ))
+            .unwrap();
+            let bar = GPTPartitionEntry::read_from(&mut f).unwrap();
+            assert!(!bar.is_unused());
+
+            let mut unused = 0;
+            let mut used = 0;
+            let mut partitions = Vec::new();
+            for i in 0..gpt.number_of_partition_entries {
+                f.seek(SeekFrom::Start(
+                    gpt.partition_entry_lba * ss
+                        + u64::from(i) * u64::from(gpt.size_of_partition_entry),
+                ))
+                .unwrap();
+                let partition = GPTPartitionEntry::read_from(&mut f).unwrap();
+
+                if partition.is_unused() {
+                    unused += 1;
+                } else {
+                    used += 1;
+                }
+
+                // NOTE: testing that serializing the PartitionName (and the whole struct) works
+                let data1 = serialize(&partition).unwrap();
+                f.seek(SeekFrom::Start(
+                    gpt.partition_entry_lba * ss
+                        + u64::from(i) * u64::from(gpt.size_of_partition_entry),
+                ))
+                .unwrap();
+                let mut data2 = vec![0; gpt.size_of_partition_entry as usize];
+                f.read_exact(&mut data2).unwrap();
+                assert_eq!(data1, data2);
+
+                partitions.push(partition);
+            }
+            assert_eq!(unused, 126);
+            assert_eq!(used, 2);
+
+            let sum = gpt.crc32_checksum;
+            gpt.update_crc32_checksum();
+            assert_eq!(gpt.crc32_checksum, sum);
+            assert_eq!(gpt.generate_crc32_checksum(), sum);
+            assert_ne!(gpt.crc32_checksum, 0);
+
+            let sum = gpt.partition_entry_array_crc32;
+            gpt.update_partition_entry_array_crc32(&partitions);
+            assert_eq!(gpt.partition_entry_array_crc32, sum);
+            assert_eq!(gpt.generate_partition_entry_array_crc32(&partitions), sum);
+            assert_ne!(gpt.partition_entry_array_crc32, 0);
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn read_and_find_from_primary() {
+        assert!(GPT::read_from(&mut fs::File::open(DISK1).unwrap(), 512).is_ok());
+        assert!(GPT::read_from(&mut fs::File::open(DISK1).unwrap(), 4096).is_err());
+        assert!(GPT::read_from(&mut fs::File::open(DISK2).unwrap(), 512).is_err());
+        assert!(GPT::read_from(&mut fs::File::open(DISK2).unwrap(), 4096).is_ok());
+        assert!(GPT::find_from(&mut fs::File::open(DISK1).unwrap()).is_ok());
+        assert!(GPT::find_from(&mut fs::File::open(DISK2).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn find_backup() {
+        fn test(path: &str, ss: u64) {
+            let mut cur = io::Cursor::new(fs::read(path).unwrap());
+            let mut gpt = GPT::read_from(&mut cur, ss).unwrap();
+            assert_eq!(gpt.header.partition_entry_lba, 2);
+            gpt.header.crc32_checksum = 1;
+            cur.seek(SeekFrom::Start(gpt.sector_size)).unwrap();
+            serialize_into(&mut cur, &gpt.header).unwrap();
+            let maybe_gpt = GPT::read_from(&mut cur, gpt.sector_size);
+            assert!(maybe_gpt.is_ok());
+            let gpt = maybe_gpt.unwrap();
+            let end = cur.seek(SeekFrom::End(0)).unwrap() / gpt.sector_size - 1;
+            assert_eq!(gpt.header.primary_lba, end);
+            assert_eq!(gpt.disk_len_sectors(), end + 1);
+            assert_eq!(gpt.header.backup_lba, 1);
+            assert_eq!(
+                gpt.header.partition_entry_lba,
+                gpt.header.last_usable_lba + 1
+            );
+            assert!(GPT::find_from(&mut cur).is_ok());
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn read_with_invalid_partition_entry_array_checksum() {
+        fn test(path: &str, ss: u64) {
+            let mut cur = io::Cursor::new(fs::read(path).unwrap());
+            let gpt = GPT::read_from(&mut cur, ss).unwrap();
+            // NOTE: corrupt the attribute bits of the first partition in both arrays
+            for lba in [2, gpt.header.last_usable_lba + 1] {
+                cur.seek(SeekFrom::Start(lba * ss + 48)).unwrap();
+                cur.write_all(&[0xff]).unwrap();
+            }
+
+            assert!(matches!(
+                GPT::read_from(&mut cur, ss),
+                Err(Error::InvalidPartitionEntryArrayChecksum(..))
+            ));
+            let (mut gpt, err) = GPT::read_from_lenient(&mut cur, ss).unwrap();
+            assert!(matches!(
+                err,
+                Some(Error::InvalidPartitionEntryArrayChecksum(..))
+            ));
+            assert_eq!(gpt[1].attribute_bits & 0xff, 0xff);
+
+            gpt.write_into(&mut cur).unwrap();
+            assert!(GPT::read_from(&mut cur, ss).is_ok());
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn read_with_non_standard_revision() {
+        fn test(path: &str, ss: u64) {
+            let mut cur = io::Cursor::new(fs::read(path).unwrap());
+            let mut gpt = GPT::read_from(&mut cur, ss).unwrap();
+            gpt.header.revision = [0x00, 0x00, 0x02, 0x00];
+            gpt.write_into(&mut cur).unwrap();
+
+            match GPT::read_from(&mut cur, ss) {
+                Err(Error::ReadError(primary_err, backup_err)) => {
+                    assert!(matches!(*primary_err, Error::InvalidRevision));
+                    assert!(matches!(*backup_err, Error::InvalidRevision));
+                }
+                x => panic!("unexpected result: {:?}", x),
+            }
+            let (mut gpt, err) = GPT::read_from_lenient(&mut cur, ss).unwrap();
+            assert!(matches!(err, Some(Error::InvalidRevision)));
+            assert!(gpt.is_primary());
+            assert!(!gpt.header.has_standard_revision());
+
+            let backup = gpt.write_into(&mut cur).unwrap();
+            assert_eq!(backup.revision, [0x00, 0x00, 0x02, 0x00]);
+            let (gpt, _) = GPT::read_from_lenient(&mut cur, ss).unwrap();
+            assert_eq!(gpt.header.revision, [0x00, 0x00, 0x02, 0x00]);
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn read_header_at_arbitrary_lba() {
+        fn test(path: &str, ss: u64) {
+            let mut data = fs::read(path).unwrap();
+            let len = data.len();
+            data.resize(len * 2, 0);
+            let mut cur = io::Cursor::new(data);
+            let gpt = GPT::read_from(&mut cur, ss).unwrap();
+            let lba = len as u64 / ss + 10;
+
+            let mut header = gpt.header.clone();
+            header.primary_lba = lba;
+            header.partition_entry_lba = lba + 1;
+            header.write_into(&mut cur, ss, &gpt.partitions).unwrap();
+
+            let copy = GPT::from_reader_at(&mut cur, ss, lba).unwrap();
+            assert_eq!(copy.header, header);
+            assert!(copy.iter().eq(gpt.iter()));
+
+            assert!(matches!(
+                GPT::from_reader_at(&mut cur, ss, 0),
+                Err(Error::InvalidSignature)
+            ));
+            cur.seek(SeekFrom::Start((lba + 1) * ss + 48)).unwrap();
+            cur.write_all(&[0xff]).unwrap();
+            assert!(matches!(
+                GPT::from_reader_at(&mut cur, ss, lba),
+                Err(Error::InvalidPartitionEntryArrayChecksum(..))
+            ));
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn error_source_chain() {
+        use std::error::Error as _;
+
+        let err = Error::ReadError(
+            Box::new(Error::Io(io::Error::new(io::ErrorKind::Other, "primary"))),
+            Box::new(Error::InvalidSignature),
+        );
+        let mut chain = vec![err.to_string()];
+        let mut source = err.source();
+        while let Some(err) = source {
+            chain.push(err.to_string());
+            source = err.source();
+        }
+
+        assert_eq!(
+            chain,
+            [
+                "could not read primary header (generic I/O error) nor backup header (invalid \
+                signature)",
+                "generic I/O error",
+                "primary",
+            ]
+        );
+    }
+
+    #[test]
+    fn read_with_absurd_number_of_partition_entries() {
+        fn test(path: &str, ss: u64) {
+            let mut cur = io::Cursor::new(fs::read(path).unwrap());
+            let gpt = GPT::read_from(&mut cur, ss).unwrap();
+            for lba in [gpt.header.primary_lba, gpt.header.backup_lba] {
+                cur.seek(SeekFrom::Start(lba * ss)).unwrap();
+                let mut header = GPTHeader::read_from(&mut cur).unwrap();
+                header.number_of_partition_entries = 4_000_000_000;
+                header.update_crc32_checksum();
+                cur.seek(SeekFrom::Start(lba * ss)).unwrap();
+                serialize_into(&mut cur, &header).unwrap();
+            }
+
+            match GPT::read_from(&mut cur, ss) {
+                Err(Error::ReadError(primary_err, backup_err)) => {
+                    assert!(matches!(
+                        *primary_err,
+                        Error::InvalidNumberOfPartitionEntries(4_000_000_000)
+                    ));
+                    assert!(matches!(
+                        *backup_err,
+                        Error::InvalidNumberOfPartitionEntries(4_000_000_000)
+                    ));
+                }
+                x => panic!("unexpected result: {:?}", x),
+            }
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn fix_wrong_backup_array_lba() {
+        fn test(path: &str, ss: u64) {
+            let mut cur = io::Cursor::new(fs::read(path).unwrap());
+            cur.get_mut()[ss as usize] ^= 0xff;
+            let mut gpt = GPT::read_from(&mut cur, ss).unwrap();
+            assert!(gpt.header.is_backup());
+            assert!(gpt.verify().is_ok());
+            let partition_entry_lba = gpt.header.partition_entry_lba;
+            assert!(!gpt.fix_backup_array_lba());
+
+            // NOTE: write a backup header with an array overlapping the end of the usable sectors
+            gpt.header.partition_entry_lba = gpt.header.last_usable_lba - 1;
+            let partitions = gpt.iter().map(|(_, x)| x.clone()).collect::<Vec<_>>();
+            gpt.header.write_into(&mut cur, ss, &partitions).unwrap();
+
+            let mut gpt = GPT::read_from(&mut cur, ss).unwrap();
+            assert_eq!(
+                gpt.header.partition_entry_lba,
+                gpt.header.last_usable_lba - 1
+            );
+            assert!(matches!(
+                gpt.verify(),
+                Err(Error::InvalidPartitionBoundaries)
+            ));
+
+            assert!(gpt.fix_backup_array_lba());
+            assert_eq!(gpt.header.partition_entry_lba, partition_entry_lba);
+            assert!(gpt.verify().is_ok());
+            gpt.write_into(&mut cur).unwrap();
+
+            let primary = GPT::read_from(&mut cur, ss).unwrap();
+            assert!(primary.header.is_primary());
+            assert_eq!(primary.header.partition_entry_lba, 2);
+            assert_eq!(primary.partitions, gpt.partitions);
+            cur.get_mut()[ss as usize] ^= 0xff;
+            let backup = GPT::read_from(&mut cur, ss).unwrap();
+            assert_eq!(backup.header, gpt.header);
+            assert_eq!(backup.partitions, gpt.partitions);
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn write_from_backup_with_asymmetric_array() {
+        fn test(path: &str, ss: u64) {
+            let mut cur = io::Cursor::new(fs::read(path).unwrap());
+            let original = GPT::read_from(&mut cur, ss).unwrap();
+            let partitions_data = |cur: &io::Cursor<Vec<u8>>| {
+                original
+                    .iter()
+                    .filter(|(_, x)| x.is_used())
+                    .map(|(_, x)| {
+                        cur.get_ref()[(x.starting_lba * ss) as usize..]
+                            [..x.size_bytes(ss).unwrap() as usize]
+                            .to_vec()
+                    })
+                    .collect::<Vec<_>>()
+            };
+            let data = partitions_data(&cur);
+
+            // NOTE: leave some space at the end of the disk and write a backup header with its
+            //       array right after the last usable sector (instead of right before the header)
+            cur.get_mut()[ss as usize] ^= 0xff;
+            let mut gpt = GPT::read_from(&mut cur, ss).unwrap();
+            gpt.set_last_usable_lba(gpt[2].ending_lba + 1).unwrap();
+            gpt.header.partition_entry_lba = gpt.header.last_usable_lba + 1;
+            assert_ne!(
+                gpt.header.partition_entry_lba + gpt.partition_array_sectors(),
+                gpt.header.primary_lba
+            );
+            let partitions = gpt.iter().map(|(_, x)| x.clone()).collect::<Vec<_>>();
+            gpt.header.write_into(&mut cur, ss, &partitions).unwrap();
+
+            let mut gpt = GPT::read_from(&mut cur, ss).unwrap();
+            assert!(gpt.header.is_backup());
+            assert_eq!(
+                gpt.header.partition_entry_lba,
+                gpt.header.last_usable_lba + 1
+            );
+            assert!(gpt.verify().is_ok());
+            gpt.write_into(&mut cur).unwrap();
+            assert_eq!(partitions_data(&cur), data);
+
+            let primary = GPT::read_from(&mut cur, ss).unwrap();
+            assert!(primary.header.is_primary());
+            assert_eq!(primary.header.partition_entry_lba, 2);
+            assert_eq!(primary.header.first_usable_lba, gpt.header.first_usable_lba);
+            assert_eq!(primary.header.last_usable_lba, gpt.header.last_usable_lba);
+            assert_eq!(primary.partitions, original.partitions);
+
+            cur.get_mut()[ss as usize] ^= 0xff;
+            let backup = GPT::read_from(&mut cur, ss).unwrap();
+            assert_eq!(backup.header, gpt.header);
+            assert_eq!(backup.partitions, original.partitions);
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn read_with_options() {
+        fn test(path: &str, ss: u64) {
+            let mut cur = io::Cursor::new(fs::read(path).unwrap());
+            let gpt = GPT::read_from(&mut cur, ss).unwrap();
+            let primary = ss as usize;
+            let array = (gpt.header.partition_entry_lba * ss) as usize;
+
+            let read = |cur: &mut io::Cursor<Vec<u8>>, f: fn(&mut ReadOptions)| {
+                let mut options = ReadOptions::default();
+                f(&mut options);
+                GPT::read_from_with_options(cur, ss, options)
+            };
+
+            // NOTE: damage the name of the first partition in the primary partition entry array
+            cur.get_mut()[array + 56] ^= 0xff;
+            assert!(matches!(
+                read(&mut cur, |_| ()),
+                Err(Error::InvalidPartitionEntryArrayChecksum(..))
+            ));
+            let damaged = read(&mut cur, |x| x.verify_array_crc = false).unwrap();
+            assert_ne!(damaged[1].partition_name, gpt[1].partition_name);
+            assert_eq!(damaged[2], gpt[2]);
+            cur.get_mut()[array + 56] ^= 0xff;
+
+            // NOTE: damage the checksum of the primary header
+            cur.get_mut()[primary + 16] ^= 0xff;
+            let backup = read(&mut cur, |_| ()).unwrap();
+            assert!(backup.header.is_backup());
+            assert!(matches!(
+                read(&mut cur, |x| x.try_backup = false),
+                Err(Error::InvalidChecksum(..))
+            ));
+            let unchecked = read(&mut cur, |x| {
+                x.try_backup = false;
+                x.verify_header_crc = false;
+            })
+            .unwrap();
+            assert!(unchecked.header.is_primary());
+            assert_eq!(unchecked.partitions, gpt.partitions);
+
+            // NOTE: the signature is still verified
+            cur.get_mut()[primary] ^= 0xff;
+            assert!(matches!(
+                read(&mut cur, |x| {
+                    x.try_backup = false;
+                    x.verify_header_crc = false;
+                }),
+                Err(Error::InvalidSignature)
+            ));
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn partition_entries_larger_than_128_bytes() {
+        fn test(path: &str, ss: u64) {
+            let mut cur = io::Cursor::new(fs::read(path).unwrap());
+            let mut gpt = GPT::read_from(&mut cur, ss).unwrap();
+
+            // NOTE: keep the same partition entry array size with 256 bytes long entries
+            gpt.header.size_of_partition_entry = 256;
+            gpt.header.number_of_partition_entries = 64;
+            let partitions = gpt
+                .iter()
+                .take(64)
+                .map(|(_, x)| x.clone())
+                .collect::<Vec<_>>();
+            gpt.header.write_into(&mut cur, ss, &partitions).unwrap();
+
+            let data = cur.get_ref();
+            let array = (gpt.header.partition_entry_lba * ss) as usize;
+            assert!(data[array + 128..array + 256].iter().all(|x| *x == 0));
+            let second = GPTPartitionEntry::read_from(&mut &data[array + 256..]).unwrap();
+            assert_eq!(second, partitions[1]);
+
+            let mut read = GPT::read_from(&mut cur, ss).unwrap();
+            assert_eq!(read.header, gpt.header);
+            assert_eq!(read.partitions, partitions);
+            read.write_into(&mut cur).unwrap();
+            assert_eq!(GPT::read_from(&mut cur, ss).unwrap(), read);
+
+            for size in [0, 127, 129, 384] {
+                gpt.header.size_of_partition_entry = size;
+                gpt.header.write_into(&mut cur, ss, &partitions).unwrap();
+                cur.seek(SeekFrom::Start(ss)).unwrap();
+                assert!(matches!(
+                    GPTHeader::read_from(&mut cur),
+                    Err(Error::InvalidPartitionEntrySize(x)) if x == size
+                ));
+            }
+
+            // NOTE: the array must not be bigger than 2^20 entries of 128 bytes
+            for (n, size, valid) in [
+                (1, 1 << 31, false),
+                (1 << 20, 128, true),
+                (1 << 19, 256, true),
+                (1 << 19, 512, false),
+                (128, 1 << 20, true),
+                (128, 1 << 21, false),
+            ] {
+                let mut header = gpt.header.clone();
+                header.number_of_partition_entries = n;
+                header.size_of_partition_entry = size;
+                header.update_crc32_checksum();
+                // NOTE: only the header is wrote, the array would be too big
+                let mut cur = io::Cursor::new(serialize(&header).unwrap());
+                let result = GPTHeader::read_from(&mut cur);
+                if valid {
+                    assert!(result.is_ok());
+                } else {
+                    assert!(matches!(
+                        result,
+                        Err(Error::InvalidPartitionEntrySize(x)) if x == size
+                    ));
+                }
+            }
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn read_headers_only() {
+        fn test(path: &str, ss: u64) {
+            let mut cur = io::Cursor::new(fs::read(path).unwrap());
+            let gpt = GPT::read_from(&mut cur, ss).unwrap();
+            assert_eq!(
+                GPT::read_headers_only(&mut cur, ss).ok(),
+                Some(gpt.header.clone())
+            );
+
+            // NOTE: the partition entry array is not read
+            let array = (gpt.header.partition_entry_lba * ss) as usize;
+            cur.get_mut()[array] ^= 0xff;
+            assert!(GPT::read_from(&mut cur, ss).is_err());
+            assert_eq!(
+                GPT::read_headers_only(&mut cur, ss).ok(),
+                Some(gpt.header.clone())
+            );
+
+            // NOTE: falls back to the backup header
+            cur.get_mut()[ss as usize] ^= 0xff;
+            let header = GPT::read_headers_only(&mut cur, ss).unwrap();
+            assert!(header.is_backup());
+            assert_eq!(header.disk_guid, gpt.header.disk_guid);
+
+            let len = cur.get_ref().len() as u64;
+            cur.get_mut()[(len - ss) as usize] ^= 0xff;
+            assert!(matches!(
+                GPT::read_headers_only(&mut cur, ss),
+                Err(Error::InvalidSignature)
+            ));
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn read_with_len() {
+        fn test(path: &str, ss: u64) {
+            let data = fs::read(path).unwrap();
+            let len = data.len() as u64;
+            let mut cur = io::Cursor::new(data);
+            let gpt = GPT::read_from(&mut cur, ss).unwrap();
+            // NOTE: corrupt the primary header so the backup header must be located
+            cur.seek(SeekFrom::Start(ss)).unwrap();
+            cur.write_all(&[0; 8]).unwrap();
+
+            let backup = GPT::read_from_with_len(&mut cur, ss, len).unwrap();
+            assert!(backup.is_backup());
+            assert!(backup.iter().eq(gpt.iter()));
+
+            // the reader is padded: seeking to the end doesn't give the size of the disk
+            cur.get_mut().resize(len as usize * 2, 0);
+            assert!(GPT::read_from(&mut cur, ss).is_err());
+            assert!(GPT::read_from_with_len(&mut cur, ss, len).is_ok());
+            assert!(GPT::read_from_with_len(&mut cur, ss, 0).is_err());
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn read_relocated_primary_header() {
+        fn test(path: &str, ss: u64) {
+            let mut cur = io::Cursor::new(fs::read(path).unwrap());
+            let mut gpt = GPT::read_from(&mut cur, ss).unwrap();
+            let backup_lba = gpt.header.backup_lba;
+            // NOTE: move the primary header (and its partition entry array) one sector further
+            let first_usable_lba = gpt.header.first_usable_lba;
+            gpt.header.primary_lba = 2;
+            gpt.header.partition_entry_lba = 3;
+            gpt.header
+                .write_into(&mut cur, ss, &gpt.partitions)
+                .unwrap();
+
+            cur.seek(SeekFrom::Start(gpt.header.primary_lba * ss))
+                .unwrap();
+            let mut header = GPTHeader::read_from(&mut cur).unwrap();
+            assert!(header.is_primary());
+            assert!(!header.is_backup());
+            header.update_from(&mut cur, ss).unwrap();
+            assert_eq!(header.primary_lba, 2);
+            assert_eq!(header.backup_lba, backup_lba);
+            assert_eq!(header.partition_entry_lba, 3);
+            assert_eq!(header.first_usable_lba, first_usable_lba + 1);
+
+            cur.seek(SeekFrom::Start(backup_lba * ss)).unwrap();
+            let header = GPTHeader::read_from(&mut cur).unwrap();
+            assert!(!header.is_primary());
+            assert!(header.is_backup());
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn entry_locations() {
+        fn test(path: &str, ss: u64) {
+            let mut cur = io::Cursor::new(fs::read(path).unwrap());
+            let mut gpt = GPT::read_from(&mut cur, ss).unwrap();
+            let n = gpt.header.number_of_partition_entries;
+            assert!(gpt.entry_location(0).is_err());
+            assert!(gpt.entry_location(n + 1).is_err());
+            assert!(gpt.backup_entry_location(n + 1).is_err());
+
+            let primary = gpt.clone();
+            gpt.header.crc32_checksum = 1;
+            cur.seek(SeekFrom::Start(ss)).unwrap();
+            serialize_into(&mut cur, &gpt.header).unwrap();
+            let backup = GPT::read_from(&mut cur, ss).unwrap();
+            assert!(backup.is_backup());
+
+            for gpt in [primary, backup] {
+                assert_eq!(gpt.entry_location(1).unwrap(), 2 * ss);
+                assert_eq!(
+                    gpt.backup_entry_location(1).unwrap(),
+                    (gpt.header.last_usable_lba + 1) * ss
+                );
+                for i in [1, 2, n] {
+                    for location in [
+                        gpt.entry_location(i).unwrap(),
+                        gpt.backup_entry_location(i).unwrap(),
+                    ] {
+                        cur.seek(SeekFrom::Start(location)).unwrap();
+                        assert_eq!(GPTPartitionEntry::read_from(&mut cur).unwrap(), gpt[i]);
+                    }
+                }
+            }
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn invalid_sector_size() {
+        for ss in [0, 1, 256, 513, 1000] {
+            let mut cur = io::Cursor::new(fs::read(DISK1).unwrap());
+            assert!(matches!(
+                GPT::read_from(&mut cur, ss),
+                Err(Error::InvalidSectorSize(_))
+            ));
+            assert!(matches!(
+                GPT::new_from(&mut cur, ss, [1; 16]),
+                Err(Error::InvalidSectorSize(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn find_from_partition_or_damaged_disk() {
+        let mut cur = io::Cursor::new(vec![0; 512 * 100]);
+        assert!(matches!(
+            GPT::find_from(&mut cur),
+            Err(Error::NoPartitionTable)
+        ));
+        GPT::write_protective_mbr_into(&mut cur, 512).unwrap();
+        assert!(matches!(
+            GPT::find_from(&mut cur),
+            Err(Error::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn add_partition_left() {
+        let mut gpt = GPT::find_from(&mut fs::File::open(DISK1).unwrap()).unwrap();
+        gpt.align = 1;
+
+        assert_eq!(gpt.find_first_place(10000), None);
+        assert_eq!(gpt.find_first_place(4), Some(44));
+        assert_eq!(gpt.find_first_place(8), Some(53));
+    }
+
+    #[test]
+    fn add_partition_left_aligned() {
+        let mut gpt = GPT::find_from(&mut fs::File::open(DISK1).unwrap()).unwrap();
+
+        gpt.align = 10000;
+        assert_eq!(gpt.find_first_place(1), None);
+        gpt.align = 4;
+        assert_eq!(gpt.find_first_place(4), Some(44));
+        gpt.align = 6;
+        assert_eq!(gpt.find_first_place(4), Some(54));
+    }
+
+    #[test]
+    fn add_partition_right() {
+        let mut gpt = GPT::find_from(&mut fs::File::open(DISK2).unwrap()).unwrap();
+        gpt.align = 1;
+
+        assert_eq!(gpt.find_last_place(10000), None);
+        assert_eq!(gpt.find_last_place(5), Some(90));
+        assert_eq!(gpt.find_last_place(20), Some(50));
+    }
+
+    #[test]
+    fn add_partition_right_aligned() {
+        let mut gpt = GPT::find_from(&mut fs::File::open(DISK2).unwrap()).unwrap();
+
+        gpt.align = 10000;
+        assert_eq!(gpt.find_last_place(1), None);
+        gpt.align = 4;
+        assert_eq!(gpt.find_last_place(5), Some(88));
+        gpt.align = 8;
+        assert_eq!(gpt.find_last_place(20), Some(48));
+
+        // NOTE: special case where there is just enough space but it's not aligned
+        gpt.align = 1;
+        assert_eq!(gpt.find_last_place(54), Some(16));
+        assert_eq!(gpt.find_last_place(55), None);
+        gpt.align = 10;
+        assert_eq!(gpt.find_last_place(54), None);
+    }
+
+    #[test]
+    fn add_partition_optimal() {
+        let mut gpt = GPT::find_from(&mut fs::File::open(DISK2).unwrap()).unwrap();
+        gpt.align = 1;
+
+        assert_eq!(gpt.find_optimal_place(10000), None);
+        assert_eq!(gpt.find_optimal_place(5), Some(80));
+        assert_eq!(gpt.find_optimal_place(20), Some(16));
+    }
+
+    #[test]
+    fn add_partition_optimal_aligned() {
+        let mut gpt = GPT::find_from(&mut fs::File::open(DISK2).unwrap()).unwrap();
+
+        gpt.align = 10000;
+        assert_eq!(gpt.find_optimal_place(1), None);
+        gpt.align = 6;
+        assert_eq!(gpt.find_optimal_place(5), Some(84));
+        gpt.align = 9;
+        assert_eq!(gpt.find_optimal_place(20), Some(18));
+    }
+
+    #[test]
+    fn find_free_sectors_with_invalid_header() {
+        let mut gpt = GPT::read_from(&mut fs::File::open(DISK1).unwrap(), 512).unwrap();
+        gpt.header.first_usable_lba = 0;
+        assert!(gpt.find_free_sectors().is_empty());
+        assert!(matches!(
+            gpt.get_maximum_partition_size(),
+            Err(Error::NoSpaceLeft)
+        ));
+
+        gpt.header.first_usable_lba = 34;
+        gpt.header.last_usable_lba = u64::MAX;
+        assert!(gpt.find_free_sectors().is_empty());
+    }
+
+    #[test]
+    fn find_free_sectors_with_used_partition_without_size() {
+        let mut gpt = GPT::read_from(&mut fs::File::open(DISK1).unwrap(), 512).unwrap();
+        gpt.align = 1;
+        let expected = gpt.find_free_sectors();
+
+        gpt[3] = GPTPartitionEntry {
+            partition_type_guid: [0xff; 16],
+            unique_partition_guid: [0xfe; 16],
+            starting_lba: 60,
+            ending_lba: 45,
+            attribute_bits: 0,
+            partition_name: "Broken".into(),
+        };
+
+        assert!(gpt[3].is_used());
+        assert!(gpt[3].size().is_err());
+        assert!(gpt.verify().is_err());
+        assert_eq!(gpt.find_free_sectors(), expected);
+        assert_eq!(
+            gpt.max_growth(1).ok(),
+            Some(gpt[2].starting_lba - gpt[1].ending_lba - 1)
+        );
+    }
+
+    #[test]
+    fn resize_partition() {
+        let mut gpt = GPT::read_from(&mut fs::File::open(DISK1).unwrap(), 512).unwrap();
+        let original = gpt.clone();
+
+        assert!(matches!(
+            gpt.resize_partition(3, 1),
+            Err(Error::UnusedPartition)
+        ));
+        assert!(gpt.resize_partition(0, 1).is_err());
+        assert!(matches!(
+            gpt.resize_partition(2, gpt.header.last_usable_lba - gpt[2].starting_lba + 2),
+            Err(Error::InvalidPartitionBoundaries)
+        ));
+        assert!(matches!(
+            gpt.resize_partition(2, u64::MAX),
+            Err(Error::Overflow)
+        ));
+        assert_eq!(gpt, original);
+
+        gpt.resize_partition(2, gpt.header.last_usable_lba - gpt[2].starting_lba + 1)
+            .unwrap();
+        assert_eq!(gpt[2].ending_lba, gpt.header.last_usable_lba);
+        gpt.resize_partition(2, 1).unwrap();
+        assert_eq!(gpt[2].starting_lba, original[2].starting_lba);
+        assert_eq!(gpt[2].ending_lba, gpt[2].starting_lba);
+    }
+
+    #[test]
+    fn trim_unused_trailing_entries() {
+        let mut gpt = GPT::read_from(&mut fs::File::open(DISK2).unwrap(), 4096).unwrap();
+        gpt.clear();
+        assert_eq!(gpt.trim_unused_trailing_entries().ok(), Some(32));
+        assert_eq!(gpt.header.first_usable_lba, 3);
+        assert_eq!(gpt.trim_unused_trailing_entries().ok(), Some(32));
+
+        let mut gpt = GPT::read_from(&mut fs::File::open(DISK1).unwrap(), 512).unwrap();
+        gpt[5] = GPTPartitionEntry {
+            starting_lba: 54,
+            ending_lba: 60,
+            unique_partition_guid: [0xee; 16],
+            ..gpt[1].clone()
+        };
+        assert_eq!(gpt.trim_unused_trailing_entries().ok(), Some(8));
+        assert_eq!(gpt.header.first_usable_lba, 4);
+        assert_eq!(gpt.header.last_usable_lba, 96);
+        assert!(gpt.verify().is_ok());
+    }
+
+    #[test]
+    fn set_first_usable_lba() {
+        let mut gpt = GPT::read_from(&mut fs::File::open(DISK1).unwrap(), 512).unwrap();
+        let original = gpt.clone();
+
+        assert!(gpt.set_first_usable_lba(33).is_err());
+        assert!(gpt.set_first_usable_lba(35).is_err());
+        assert!(gpt.set_first_usable_lba(67).is_err());
+        assert_eq!(gpt, original);
+
+        gpt.header.partition_entry_lba = u64::MAX;
+        assert!(matches!(gpt.set_first_usable_lba(40), Err(Error::Overflow)));
+        gpt.header.partition_entry_lba = original.header.partition_entry_lba;
+
+        gpt.clear();
+        gpt.set_first_usable_lba(67).unwrap();
+        assert!(gpt.find_free_sectors().is_empty());
+        gpt.set_first_usable_lba(34).unwrap();
+        assert!(gpt.verify().is_ok());
+        assert!(gpt.set_first_usable_lba(68).is_err());
+    }
+
+    #[test]
+    fn verify_duplicate_partition_guids() {
+        let mut gpt = GPT::read_from(&mut fs::File::open(DISK1).unwrap(), 512).unwrap();
+        assert!(gpt.verify().is_ok());
+
+        // NOTE: the GUIDs of the unused entries are not checked
+        gpt[3].unique_partition_guid = gpt[1].unique_partition_guid;
+        gpt[4].unique_partition_guid = gpt[1].unique_partition_guid;
+        assert!(gpt.verify().is_ok());
+
+        gpt[3] = GPTPartitionEntry {
+            starting_lba: 54,
+            ending_lba: 55,
+            ..gpt[2].clone()
+        };
+        assert!(matches!(gpt.verify(), Err(Error::ConflictPartitionGUID)));
+        assert!(matches!(
+            gpt.write_into(&mut io::Cursor::new(fs::read(DISK1).unwrap())),
+            Err(Error::ConflictPartitionGUID)
+        ));
+
+        gpt[3].unique_partition_guid = [0xee; 16];
+        assert!(gpt.verify().is_ok());
+    }
+
+    #[test]
+    fn verify_partition_before_first_usable_lba() {
+        let mut gpt = GPT::read_from(&mut fs::File::open(DISK1).unwrap(), 512).unwrap();
+        gpt.header.first_usable_lba = 49;
+        assert!(matches!(
+            gpt.verify(),
+            Err(Error::PartitionBeforeFirstUsableLba(1))
+        ));
+
+        gpt[1] = GPTPartitionEntry::empty();
+        assert!(matches!(
+            gpt.verify(),
+            Err(Error::PartitionBeforeFirstUsableLba(2))
+        ));
+        assert!(matches!(
+            gpt.write_into(&mut io::Cursor::new(fs::read(DISK1).unwrap())),
+            Err(Error::PartitionBeforeFirstUsableLba(2))
+        ));
+
+        gpt.header.first_usable_lba = 48;
+        assert!(gpt.verify().is_ok());
+    }
+
+    #[test]
+    fn validate_against_disk() {
+        fn test(path: &str, ss: u64) {
+            let mut cur = io::Cursor::new(fs::read(path).unwrap());
+            let disk_sectors = cur.get_ref().len() as u64 / ss;
+            let mut gpt = GPT::read_from(&mut cur, ss).unwrap();
+            assert!(gpt.validate_against_disk(&mut cur).is_ok());
+
+            // NOTE: a trailing partial sector is not part of the disk
+            let mut bigger = io::Cursor::new(vec![0; (disk_sectors * ss + ss - 1) as usize]);
+            assert!(gpt.validate_against_disk(&mut bigger).is_ok());
+
+            for found in [disk_sectors - 1, disk_sectors + 1, 0] {
+                let mut other = io::Cursor::new(vec![0; (found * ss) as usize]);
+                assert!(matches!(
+                    gpt.validate_against_disk(&mut other),
+                    Err(Error::DiskSizeMismatch { expected, found: x })
+                        if expected == disk_sectors && x == found
+                ));
+            }
+
+            // NOTE: the same checks apply when the GPT has been read from the backup header
+            cur.get_mut()[ss as usize] ^= 0xff;
+            let backup = GPT::read_from(&mut cur, ss).unwrap();
+            assert!(backup.header.is_backup());
+            assert!(backup.validate_against_disk(&mut cur).is_ok());
+
+            gpt.header.last_usable_lba = gpt.backup_partition_entry_lba();
+            assert!(matches!(
+                gpt.validate_against_disk(&mut cur),
+                Err(Error::InvalidPartitionBoundaries)
+            ));
+            gpt.header.last_usable_lba -= 1;
+            assert!(gpt.validate_against_disk(&mut cur).is_ok());
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn disk_usage_breakdown() {
+        fn test(path: &str, ss: u64) {
+            let disk_len = fs::metadata(path).unwrap().len();
+            let mut gpt = GPT::read_from(&mut fs::File::open(path).unwrap(), ss).unwrap();
+            let usage = gpt.disk_usage_breakdown(disk_len).unwrap();
+            assert_eq!(usage.primary_metadata, gpt.header.first_usable_lba * ss);
+            assert_eq!(
+                usage.backup_metadata,
+                disk_len - (gpt.header.last_usable_lba + 1) * ss
+            );
+            assert_eq!(
+                usage.primary_metadata + usage.free + usage.allocated + usage.backup_metadata,
+                disk_len
+            );
+
+            // NOTE: an empty partition table has no allocated byte
+            let mut empty = gpt.clone();
+            for i in 1..=empty.header.number_of_partition_entries {
+                empty[i] = GPTPartitionEntry::empty();
+            }
+            let empty_usage = empty.disk_usage_breakdown(disk_len).unwrap();
+            assert_eq!(empty_usage.allocated, 0);
+            assert_eq!(empty_usage.free, usage.free + usage.allocated);
+
+            // NOTE: the usable sectors must fit in the disk
+            let min_len = (gpt.header.last_usable_lba + 1) * ss;
+            assert_eq!(
+                gpt.disk_usage_breakdown(min_len).unwrap().backup_metadata,
+                0
+            );
+            assert!(matches!(
+                gpt.disk_usage_breakdown(min_len - 1),
+                Err(Error::DiskTooSmall { required, found })
+                    if required == min_len && found == min_len - 1
+            ));
+
+            gpt[1].ending_lba = gpt[2].starting_lba;
+            assert!(matches!(
+                gpt.disk_usage_breakdown(disk_len),
+                Err(Error::InvalidPartitionBoundaries)
+            ));
+            gpt[1].ending_lba = gpt[1].starting_lba - 1;
+            assert!(matches!(
+                gpt.disk_usage_breakdown(disk_len),
+                Err(Error::InvalidPartitionBoundaries)
+            ));
+            gpt[1] = GPTPartitionEntry::empty();
+            gpt[2].ending_lba = gpt.header.last_usable_lba + 1;
+            assert!(matches!(
+                gpt.disk_usage_breakdown(disk_len),
+                Err(Error::InvalidPartitionBoundaries)
+            ));
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn set_align_bytes() {
+        fn test(ss: u64) {
+            let mut cur = io::Cursor::new(vec![0; 100 * ss as usize]);
+            let mut gpt = GPT::new_from(&mut cur, ss, [0xff; 16]).unwrap();
+
+            gpt.set_align_bytes(ss).unwrap();
+            assert_eq!(gpt.align, 1);
+            assert_eq!(gpt.align_in_bytes(), ss);
+            gpt.set_align_bytes(1024 * 1024).unwrap();
+            assert_eq!(gpt.align, 1024 * 1024 / ss);
+            assert_eq!(gpt.align_in_bytes(), 1024 * 1024);
+
+            // NOTE: the alignment is left untouched on error
+            for bytes in [0, ss - 1, ss + 1, ss / 2, u64::MAX] {
+                assert!(matches!(
+                    gpt.set_align_bytes(bytes),
+                    Err(Error::InvalidAlignment(x)) if x == bytes
+                ));
+                assert_eq!(gpt.align, 1024 * 1024 / ss);
+            }
+        }
+
+        test(512);
+        test(4096);
+    }
+
+    #[test]
+    fn is_range_free() {
+        let mut gpt = GPT::find_from(&mut fs::File::open(DISK1).unwrap()).unwrap();
+        assert_eq!(gpt.header.last_usable_lba, 66);
+
+        // NOTE: an empty range is never free
+        assert!(!gpt.is_range_free(45, 44));
+        assert!(gpt.is_range_free(44, 44));
+        assert!(gpt.is_range_free(44, 47));
+        assert!(!gpt.is_range_free(43, 47));
+        assert!(!gpt.is_range_free(44, 48));
+        assert!(!gpt.is_range_free(33, 33));
+
+        assert!(gpt.is_range_free(53, 66));
+        assert!(gpt.is_range_free(66, 66));
+        assert!(!gpt.is_range_free(53, 67));
+        assert!(!gpt.is_range_free(67, 67));
+        assert!(!gpt.is_range_free(53, u64::MAX));
+
+        // NOTE: the unused entries are ignored, even if they overlap each other
+        for i in [3, 4] {
+            gpt[i] = GPTPartitionEntry {
+                partition_type_guid: [0; 16],
+                unique_partition_guid: [i as u8; 16],
+                starting_lba: 53,
+                ending_lba: 66,
+                attribute_bits: 0,
+                partition_name: "".into(),
+            };
+        }
+        assert!(!gpt[3].is_used());
+        assert!(gpt.is_range_free(53, 66));
+
+        gpt[3].partition_type_guid = [0xff; 16];
+        assert!(!gpt.is_range_free(53, 66));
+        assert!(!gpt.is_range_free(66, 66));
+        assert!(gpt.is_range_free(44, 47));
     }
 
-    /// This function writes a protective MBR in the first sector of the disk
-    /// starting at byte 446 and ending at byte 511. Any existing data will be overwritten.
-    /// This function differs from [`Self::write_protective_mbr_into`] in that the partition in the
-    /// MBR partition table is marked as bootable. Some legacy BIOS systems do not consider a disk
-    /// to be bootable if there isn't an MBR partition marked as bootable in the MBR partition
-    /// table.
-    ///
-    /// <div class="warning">Some systems will not consider a disk to be bootable in UEFI mode
-    /// if the pMBR is marked as bootable, so this should only be used if booting on legacy BIOS
-    /// systems is a requirement.</div>
-    pub fn write_bootable_protective_mbr_into<W: ?Sized>(
-        mut writer: &mut W,
-        sector_size: u64,
-    ) -> Result<()>
-    where
-        W: Write + Seek,
-    {
-        Self::write_protective_mbr_into_impl(&mut writer, sector_size, true)
+    #[test]
+    fn equivalent_gpts() {
+        let mut cur = io::Cursor::new(fs::read(DISK1).unwrap());
+        let gpt = GPT::read_from(&mut cur, 512).unwrap();
+
+        // NOTE: the backup copy is equivalent
+        cur.get_mut()[512] ^= 0xff;
+        let backup = GPT::read_from(&mut cur, 512).unwrap();
+        assert_ne!(gpt, backup);
+        assert!(gpt.equivalent_to(&backup));
+
+        let mut other = gpt.clone();
+        other.set_number_of_partition_entries(4).unwrap();
+        assert!(!gpt.equivalent_to(&other));
+        other.header.first_usable_lba = gpt.header.first_usable_lba;
+        other.header.last_usable_lba = gpt.header.last_usable_lba;
+        assert!(gpt.equivalent_to(&other));
+
+        let mut other = gpt.clone();
+        other[3] = other[2].clone();
+        assert!(!gpt.equivalent_to(&other));
+        assert!(!other.equivalent_to(&gpt));
+
+        // NOTE: same entries in a different order and in different slots
+        let mut other = gpt.clone();
+        other[5] = gpt[1].clone();
+        other[1] = gpt[2].clone();
+        other[2] = GPTPartitionEntry::empty();
+        assert!(gpt.equivalent_to(&other));
+
+        // NOTE: [A, A, B] and [A, B, B] have the same entries but not the same layout
+        let mut a = gpt.clone();
+        a[3] = gpt[1].clone();
+        let mut b = gpt.clone();
+        b[3] = gpt[2].clone();
+        assert!(!a.equivalent_to(&b));
+        assert!(!b.equivalent_to(&a));
+        assert!(a.equivalent_to(&a.clone()));
+
+        let mut other = gpt.clone();
+        other.header.disk_guid = [0xff; 16];
+        assert!(!gpt.equivalent_to(&other));
+
+        let other = gpt.clone().with_sector_size(4096).unwrap();
+        assert!(!gpt.equivalent_to(&other));
     }
 
-    fn write_protective_mbr_into_impl<W: ?Sized>(
-        mut writer: &mut W,
-        sector_size: u64,
-        bootable: bool,
-    ) -> Result<()>
-    where
-        W: Write + Seek,
-    {
-        let size = writer.seek(SeekFrom::End(0))? / sector_size - 1;
-        writer.seek(SeekFrom::Start(446))?;
-        // partition 1
-        if bootable {
-            writer.write_all(&[0x80])?;
-        } else {
-            writer.write_all(&[0x00])?;
+    #[test]
+    fn new_without_reader() {
+        fn test(ss: u64, sectors: u64) {
+            let mut cur = io::Cursor::new(vec![0; (ss * sectors) as usize]);
+            let expected = GPT::new_from(&mut cur, ss, [0xff; 16]).unwrap();
+            assert_eq!(GPT::new(ss, sectors, [0xff; 16]).unwrap(), expected);
         }
-        writer.write_all(&[
-            0x00, 0x02, 0x00, // CHS address of first absolute sector
-            0xee, // partition type
-            0xff, 0xff, 0xff, // CHS address of last absolute sector
-            0x01, 0x00, 0x00, 0x00, // LBA of first absolute sector
-        ])?;
-        // number of sectors in partition 1
-        serialize_into(
-            &mut writer,
-            &(if size > u64::from(u32::max_value()) {
-                u32::max_value()
-            } else {
-                size as u32
-            }),
-        )?;
-        writer.write_all(&[0; 16])?; // partition 2
-        writer.write_all(&[0; 16])?; // partition 3
-        writer.write_all(&[0; 16])?; // partition 4
-        writer.write_all(&[0x55, 0xaa])?; // signature
 
-        Ok(())
+        test(512, 100);
+        test(512, 68);
+        test(4096, 100);
+        test(4096, 12);
+
+        assert!(matches!(
+            GPT::new(512, 67, [0xff; 16]),
+            Err(Error::InvalidPartitionBoundaries)
+        ));
+        assert!(matches!(
+            GPT::new(1000, 100, [0xff; 16]),
+            Err(Error::InvalidSectorSize(1000))
+        ));
+        assert!(GPT::new(512, 0, [0xff; 16]).is_err());
     }
 
-    /// Returns `true` if the `GPTHeader` is a primary copy (the header is located at the beginning
-    /// of the disk).
-    pub fn is_primary(&self) -> bool {
-        self.header.is_primary()
+    #[test]
+    fn max_growth() {
+        let mut gpt = GPT::find_from(&mut fs::File::open(DISK1).unwrap()).unwrap();
+        gpt.align = 1;
+
+        assert!(gpt.max_growth(0).is_err());
+        assert!(gpt.max_growth(3).is_err());
+        assert_eq!(
+            gpt.max_growth(1).ok(),
+            Some(gpt[2].starting_lba - gpt[1].ending_lba - 1)
+        );
+        assert_eq!(
+            gpt.max_growth(2).ok(),
+            Some(gpt.header.last_usable_lba - gpt[2].ending_lba)
+        );
     }
 
-    /// Returns `true` if the `GPTHeader` is a backup copy (the header is located at the end of the
-    /// disk).
-    ///
-    /// Note that when the header is a backup copy, the `primary_lba` is the LBA of the backup copy
-    /// and the `backup_lba` is the LBA of the primary copy.
-    pub fn is_backup(&self) -> bool {
-        self.header.is_backup()
+    #[test]
+    fn max_growth_aligned() {
+        let mut gpt = GPT::find_from(&mut fs::File::open(DISK2).unwrap()).unwrap();
+
+        gpt.align = 10000;
+        assert_eq!(gpt.max_growth(1).ok(), Some(0));
+        assert_eq!(gpt.max_growth(2).ok(), Some(0));
+        // NOTE: the sector following the partition is already aligned
+        gpt.align = 4;
+        assert_eq!(gpt.max_growth(1).ok(), Some(52));
+        assert_eq!(gpt.max_growth(2).ok(), Some(12));
+        // NOTE: the sector following the partition is not aligned
+        gpt.align = 6;
+        assert_eq!(gpt.max_growth(1).ok(), Some(50));
+        assert_eq!(gpt.max_growth(2).ok(), Some(10));
+        gpt.align = 1;
+        assert_eq!(gpt.max_growth(1).ok(), Some(54));
+        assert_eq!(gpt.max_growth(2).ok(), Some(15));
     }
-}
 
-impl Index<u32> for GPT {
-    type Output = GPTPartitionEntry;
+    #[test]
+    fn max_growth_without_free_space() {
+        let mut gpt = GPT::find_from(&mut fs::File::open(DISK1).unwrap()).unwrap();
+        gpt[1].ending_lba = gpt[2].starting_lba - 1;
+        gpt[2].ending_lba = gpt.header.last_usable_lba;
 
-    fn index(&self, i: u32) -> &GPTPartitionEntry {
-        assert!(i != 0, "invalid partition index: 0");
-        &self.partitions[i as usize - 1]
+        for align in [1, 2, 4, 5, 10000] {
+            gpt.align = align;
+            assert_eq!(gpt.max_growth(1).ok(), Some(0));
+            assert_eq!(gpt.max_growth(2).ok(), Some(0));
+        }
     }
-}
 
-impl IndexMut<u32> for GPT {
-    fn index_mut(&mut self, i: u32) -> &mut GPTPartitionEntry {
-        assert!(i != 0, "invalid partition index: 0");
-        &mut self.partitions[i as usize - 1]
+    #[test]
+    fn clone_partition_within() {
+        let mut gpt = GPT::find_from(&mut fs::File::open(DISK2).unwrap()).unwrap();
+        gpt.align = 1;
+
+        // overlap
+        let start = gpt[2].starting_lba - 1;
+        assert!(gpt.clone_partition_within(2, 3, start, [3; 16]).is_err());
+        // end after last_usable_lba
+        let start = gpt.header.last_usable_lba - 8;
+        assert!(gpt.clone_partition_within(2, 3, start, [3; 16]).is_err());
+        // unused source and invalid partition numbers
+        assert!(gpt.clone_partition_within(4, 3, 16, [3; 16]).is_err());
+        assert!(gpt.clone_partition_within(0, 3, 16, [3; 16]).is_err());
+        assert!(gpt.clone_partition_within(2, 129, 16, [3; 16]).is_err());
+        // conflict of partition GUIDs
+        let guid = gpt[1].unique_partition_guid;
+        assert!(gpt.clone_partition_within(2, 3, 16, guid).is_err());
+        assert!(gpt[3].is_unused());
+
+        gpt.clone_partition_within(2, 3, 16, [3; 16]).unwrap();
+        assert_eq!(gpt[3].starting_lba, 16);
+        assert_eq!(gpt[3].ending_lba, 25);
+        assert_eq!(gpt[3].partition_type_guid, gpt[2].partition_type_guid);
+        assert_eq!(gpt[3].unique_partition_guid, [3; 16]);
+
+        // move a partition onto itself
+        let start = gpt.header.last_usable_lba - 9;
+        gpt.clone_partition_within(2, 2, start, [2; 16]).unwrap();
+        assert_eq!(gpt[2].ending_lba, gpt.header.last_usable_lba);
+        gpt.write_into(&mut io::Cursor::new(fs::read(DISK2).unwrap()))
+            .unwrap();
     }
-}
 
-#[cfg(test)]
-mod test {
-    #![allow(clippy::disallowed_names)]
+    #[test]
+    fn no_free_slot_and_no_space_left() {
+        let mut gpt = GPT::find_from(&mut fs::File::open(DISK1).unwrap()).unwrap();
+        gpt.align = 1;
 
-    use super::*;
-    use std::fs;
+        for (_, partition) in gpt.iter_mut().skip(2) {
+            partition.partition_type_guid = [1; 16];
+        }
+        assert!(matches!(
+            gpt.find_free_partition_number(),
+            Err(Error::NoFreeSlot)
+        ));
+
+        gpt.remove(3).unwrap();
+        assert_eq!(gpt.find_free_partition_number().ok(), Some(3));
+        gpt[1].starting_lba = gpt.header.first_usable_lba;
+        gpt[1].ending_lba = gpt.header.last_usable_lba;
+        for i in 2..=gpt.header.number_of_partition_entries {
+            gpt.remove(i).unwrap();
+        }
+        assert!(matches!(
+            gpt.get_maximum_partition_size(),
+            Err(Error::NoSpaceLeft)
+        ));
+        assert_eq!(gpt.find_free_partition_number().ok(), Some(2));
+    }
 
-    const DISK1: &str = "tests/fixtures/disk1.img";
-    const DISK2: &str = "tests/fixtures/disk2.img";
+    #[test]
+    fn set_number_of_partition_entries_with_reserved_sectors() {
+        fn test(ss: u64) {
+            let mut cur = io::Cursor::new(vec![0; 300 * ss as usize]);
+            let mut gpt = GPT::new_from(&mut cur, ss, [0xff; 16]).unwrap();
+            let array_sectors = gpt.partition_array_sectors();
+            gpt.set_first_usable_lba(2 + array_sectors + 8).unwrap();
+            gpt.set_last_usable_lba(gpt.header.last_usable_lba - 8)
+                .unwrap();
+            let (first_usable_lba, last_usable_lba) =
+                (gpt.header.first_usable_lba, gpt.header.last_usable_lba);
+
+            // NOTE: the reserved sectors stay out of the usable sectors
+            gpt.set_number_of_partition_entries(4).unwrap();
+            assert_eq!(gpt.header.first_usable_lba, first_usable_lba);
+            assert_eq!(gpt.header.last_usable_lba, last_usable_lba);
+            gpt.set_number_of_partition_entries(128).unwrap();
+            assert_eq!(gpt.header.first_usable_lba, first_usable_lba);
+            assert_eq!(gpt.header.last_usable_lba, last_usable_lba);
+            assert!(gpt.trim_unused_trailing_entries().unwrap() < 128);
+            assert_eq!(gpt.header.first_usable_lba, first_usable_lba);
+            assert_eq!(gpt.header.last_usable_lba, last_usable_lba);
+
+            // NOTE: the partition entry arrays would overlap the reserved sectors
+            let n = gpt.header.number_of_partition_entries;
+            let too_many = ((array_sectors + 9) * ss / 128) as u32;
+            assert!(matches!(
+                gpt.set_number_of_partition_entries(too_many),
+                Err(Error::InvalidPartitionBoundaries)
+            ));
+            assert_eq!(gpt.header.number_of_partition_entries, n);
+
+            // NOTE: the usable sectors follow the arrays again once the reserved sectors are
+            //       released
+            gpt.set_first_usable_lba(2 + gpt.partition_array_sectors())
+                .unwrap();
+            gpt.set_number_of_partition_entries(128).unwrap();
+            assert_eq!(gpt.header.first_usable_lba, 2 + array_sectors);
+            assert_eq!(gpt.header.last_usable_lba, last_usable_lba);
+        }
+
+        test(512);
+        test(4096);
+    }
 
     #[test]
-    fn read_header_and_partition_entries() {
+    fn set_number_of_partition_entries() {
         fn test(path: &str, ss: u64) {
-            let mut f = fs::File::open(path).unwrap();
+            let mut cur = io::Cursor::new(fs::read(path).unwrap());
+            let mut gpt = GPT::read_from(&mut cur, ss).unwrap();
 
-            f.seek(SeekFrom::Start(ss)).unwrap();
-            let mut gpt = GPTHeader::read_from(&mut f).unwrap();
+            assert!(matches!(
+                gpt.set_number_of_partition_entries(0),
+                Err(Error::InvalidNumberOfPartitionEntries(0))
+            ));
+            let mut large_entries = gpt.clone();
+            large_entries.header.size_of_partition_entry = 256;
+            assert!(matches!(
+                large_entries.set_number_of_partition_entries(1 << 20),
+                Err(Error::InvalidNumberOfPartitionEntries(_))
+            ));
+            assert_eq!(gpt.header.number_of_partition_entries, 128);
+            // used partition entry dropped
+            assert!(matches!(
+                gpt.set_number_of_partition_entries(1),
+                Err(Error::UsedPartitionEntryDropped(2))
+            ));
+            // array overlapping the first partition
+            assert!(gpt.set_number_of_partition_entries(256).is_err());
+            assert_eq!(gpt.header.number_of_partition_entries, 128);
 
-            f.seek(SeekFrom::Start(gpt.backup_lba * ss)).unwrap();
-            assert!(GPTHeader::read_from(&mut f).is_ok());
+            gpt.set_number_of_partition_entries(2).unwrap();
+            assert_eq!(gpt.header.first_usable_lba, 3);
+            assert_eq!(gpt.header.last_usable_lba, gpt.header.backup_lba - 2);
+            gpt.write_into(&mut cur).unwrap();
 
-            f.seek(SeekFrom::Start(gpt.partition_entry_lba * ss))
-                .unwrap();
-            let foo = GPTPartitionEntry::read_from(&mut f).unwrap();
-            assert!(!foo.is_unused());
+            let gpt = GPT::read_from(&mut cur, ss).unwrap();
+            assert_eq!(gpt.header.number_of_partition_entries, 2);
+            assert_eq!(gpt.iter().count(), 2);
 
-            f.seek(SeekFrom::Start(
-                gpt.partition_entry_lba * ss + u64::from(gpt.size_of_partition_entry),
-            ))
-            .unwrap();
-            let bar = GPTPartitionEntry::read_from(&mut f).unwrap();
-            assert!(!bar.is_unused());
+            let mut gpt = gpt;
+            gpt.header.crc32_checksum = 1;
+            cur.seek(SeekFrom::Start(ss)).unwrap();
+            serialize_into(&mut cur, &gpt.header).unwrap();
+            let mut gpt = GPT::read_from(&mut cur, ss).unwrap();
+            assert!(gpt.is_backup());
+            gpt.set_number_of_partition_entries(16).unwrap();
+            assert_eq!(
+                gpt.header.partition_entry_lba,
+                gpt.header.last_usable_lba + 1
+            );
+            gpt.write_into(&mut cur).unwrap();
+            assert_eq!(
+                GPT::read_from(&mut cur, ss)
+                    .unwrap()
+                    .header
+                    .number_of_partition_entries,
+                16
+            );
+        }
 
-            let mut unused = 0;
-            let mut used = 0;
-            let mut partitions = Vec::new();
-            for i in 0..gpt.number_of_partition_entries {
-                f.seek(SeekFrom::Start(
-                    gpt.partition_entry_lba * ss
-                        + u64::from(i) * u64::from(gpt.size_of_partition_entry),
-                ))
-                .unwrap();
-                let partition = GPTPartitionEntry::read_from(&mut f).unwrap();
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
 
-                if partition.is_unused() {
-                    unused += 1;
-                } else {
-                    used += 1;
-                }
+    #[test]
+    fn set_last_usable_lba() {
+        fn test(path: &str, ss: u64) {
+            let mut cur = io::Cursor::new(fs::read(path).unwrap());
+            let mut gpt = GPT::read_from(&mut cur, ss).unwrap();
+            let last_usable_lba = gpt.header.last_usable_lba;
+            let ending_lba = gpt[2].ending_lba;
 
-                // NOTE: testing that serializing the PartitionName (and the whole struct) works
-                let data1 = serialize(&partition).unwrap();
-                f.seek(SeekFrom::Start(
-                    gpt.partition_entry_lba * ss
-                        + u64::from(i) * u64::from(gpt.size_of_partition_entry),
-                ))
-                .unwrap();
-                let mut data2 = vec![0; gpt.size_of_partition_entry as usize];
-                f.read_exact(&mut data2).unwrap();
-                assert_eq!(data1, data2);
+            // used partition extending past the new last usable LBA
+            assert!(gpt.set_last_usable_lba(ending_lba - 1).is_err());
+            // overlapping the backup partition entry array
+            assert!(gpt.set_last_usable_lba(last_usable_lba + 1).is_err());
+            assert_eq!(gpt.header.last_usable_lba, last_usable_lba);
 
-                partitions.push(partition);
-            }
-            assert_eq!(unused, 126);
-            assert_eq!(used, 2);
+            gpt.set_last_usable_lba(ending_lba).unwrap();
+            gpt.write_into(&mut cur).unwrap();
 
-            let sum = gpt.crc32_checksum;
-            gpt.update_crc32_checksum();
-            assert_eq!(gpt.crc32_checksum, sum);
-            assert_eq!(gpt.generate_crc32_checksum(), sum);
-            assert_ne!(gpt.crc32_checksum, 0);
+            let gpt = GPT::read_from(&mut cur, ss).unwrap();
+            assert!(gpt.is_primary());
+            assert_eq!(gpt.header.last_usable_lba, ending_lba);
 
-            let sum = gpt.partition_entry_array_crc32;
-            gpt.update_partition_entry_array_crc32(&partitions);
-            assert_eq!(gpt.partition_entry_array_crc32, sum);
-            assert_eq!(gpt.generate_partition_entry_array_crc32(&partitions), sum);
-            assert_ne!(gpt.partition_entry_array_crc32, 0);
+            let mut gpt = gpt;
+            gpt.header.crc32_checksum = 1;
+            cur.seek(SeekFrom::Start(ss)).unwrap();
+            serialize_into(&mut cur, &gpt.header).unwrap();
+            let gpt = GPT::read_from(&mut cur, ss).unwrap();
+            assert!(gpt.is_backup());
+            assert_eq!(gpt.header.last_usable_lba, ending_lba);
+            assert_eq!(gpt.header.partition_entry_lba, last_usable_lba + 1);
         }
 
         test(DISK1, 512);
@@ -1415,112 +5864,356 @@ mod test {
     }
 
     #[test]
-    fn read_and_find_from_primary() {
-        assert!(GPT::read_from(&mut fs::File::open(DISK1).unwrap(), 512).is_ok());
-        assert!(GPT::read_from(&mut fs::File::open(DISK1).unwrap(), 4096).is_err());
-        assert!(GPT::read_from(&mut fs::File::open(DISK2).unwrap(), 512).is_err());
-        assert!(GPT::read_from(&mut fs::File::open(DISK2).unwrap(), 4096).is_ok());
-        assert!(GPT::find_from(&mut fs::File::open(DISK1).unwrap()).is_ok());
-        assert!(GPT::find_from(&mut fs::File::open(DISK2).unwrap()).is_ok());
+    fn partition_size_bytes_overflow() {
+        let entry = GPTPartitionEntry {
+            starting_lba: 0,
+            ending_lba: u64::MAX - 1,
+            ..GPTPartitionEntry::empty()
+        };
+
+        assert_eq!(entry.size().ok(), Some(u64::MAX));
+        assert!(matches!(entry.size_bytes(4096), Err(Error::Overflow)));
     }
 
     #[test]
-    fn find_backup() {
+    fn compare_resized_gpt_with_gpt_read_back() {
         fn test(path: &str, ss: u64) {
             let mut cur = io::Cursor::new(fs::read(path).unwrap());
             let mut gpt = GPT::read_from(&mut cur, ss).unwrap();
-            assert_eq!(gpt.header.partition_entry_lba, 2);
-            gpt.header.crc32_checksum = 1;
-            cur.seek(SeekFrom::Start(gpt.sector_size)).unwrap();
-            serialize_into(&mut cur, &gpt.header).unwrap();
-            let maybe_gpt = GPT::read_from(&mut cur, gpt.sector_size);
-            assert!(maybe_gpt.is_ok());
-            let gpt = maybe_gpt.unwrap();
-            let end = cur.seek(SeekFrom::End(0)).unwrap() / gpt.sector_size - 1;
-            assert_eq!(gpt.header.primary_lba, end);
-            assert_eq!(gpt.header.backup_lba, 1);
+
+            gpt.set_number_of_partition_entries(16).unwrap();
+            let stale_partition_arrays = gpt.stale_partition_arrays.clone();
+            assert!(!stale_partition_arrays.is_empty());
+            gpt.write_into(&mut cur).unwrap();
+            gpt.stale_partition_arrays = stale_partition_arrays;
+
+            let read = GPT::read_from(&mut cur, ss).unwrap();
+            assert!(read.stale_partition_arrays.is_empty());
             assert_eq!(
-                gpt.header.partition_entry_lba,
-                gpt.header.last_usable_lba + 1
+                GPT {
+                    align: gpt.align,
+                    ..read
+                },
+                gpt
             );
-            assert!(GPT::find_from(&mut cur).is_ok());
         }
 
-        test(DISK1, 512);
-        test(DISK2, 4096);
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn zero_stale_partition_arrays() {
+        fn test(ss: u64) {
+            let mut cur = io::Cursor::new(vec![0; 256 * ss as usize]);
+            let mut gpt = GPT::new_from(&mut cur, ss, [0xff; 16]).unwrap();
+            gpt.set_number_of_partition_entries(256).unwrap();
+            let old_array_sectors = gpt.partition_array_sectors();
+            for i in [1, 2] {
+                gpt[i] = GPTPartitionEntry {
+                    partition_type_guid: [0xff; 16],
+                    unique_partition_guid: [i as u8; 16],
+                    starting_lba: gpt.header.first_usable_lba + u64::from(i) * 4,
+                    ending_lba: gpt.header.first_usable_lba + u64::from(i) * 4 + 1,
+                    attribute_bits: 0,
+                    partition_name: "Foo".into(),
+                };
+            }
+            gpt.write_into(&mut cur).unwrap();
+
+            gpt.set_number_of_partition_entries(128).unwrap();
+            let new_array_sectors = gpt.partition_array_sectors();
+            // NOTE: the stale sectors are only zeroed if they are not used by a partition
+            gpt[3] = GPTPartitionEntry {
+                unique_partition_guid: [3; 16],
+                starting_lba: 2 + new_array_sectors,
+                ending_lba: 2 + new_array_sectors,
+                ..gpt[1].clone()
+            };
+            cur.seek(SeekFrom::Start((2 + new_array_sectors) * ss))
+                .unwrap();
+            cur.write_all(&vec![0xaa; ss as usize]).unwrap();
+            gpt.write_into(&mut cur).unwrap();
+
+            let data = cur.get_ref();
+            let sector = |lba: u64| &data[(lba * ss) as usize..((lba + 1) * ss) as usize];
+            assert!(sector(2 + new_array_sectors).iter().all(|x| *x == 0xaa));
+            for lba in 3 + new_array_sectors..2 + old_array_sectors {
+                assert!(sector(lba).iter().all(|x| *x == 0));
+            }
+            let backup_lba = gpt.header.backup_lba;
+            for lba in backup_lba - old_array_sectors..backup_lba - new_array_sectors {
+                assert!(sector(lba).iter().all(|x| *x == 0));
+            }
+
+            let gpt = GPT::read_from(&mut cur, ss).unwrap();
+            assert_eq!(gpt.header.number_of_partition_entries, 128);
+            assert_eq!(gpt.used_partition_count(), 3);
+        }
+
+        test(512);
+        test(4096);
     }
 
     #[test]
-    fn add_partition_left() {
-        let mut gpt = GPT::find_from(&mut fs::File::open(DISK1).unwrap()).unwrap();
-        gpt.align = 1;
+    fn update_from_with_huge_partition_entry_array() {
+        let mut cur = io::Cursor::new(vec![0; 100 * 512]);
+        let mut header = GPTHeader::new_from(&mut cur, 512, [0xff; 16]).unwrap();
+        let expected = header.clone();
+
+        header.number_of_partition_entries = u32::MAX;
+        header.size_of_partition_entry = u32::MAX;
+        assert!(matches!(
+            header.update_from(&mut cur, 512),
+            Err(Error::InvalidPartitionBoundaries)
+        ));
+
+        header.number_of_partition_entries = 128;
+        header.size_of_partition_entry = 128;
+        header.partition_entry_lba = u64::MAX;
+        assert!(matches!(
+            header.update_from(&mut cur, 512),
+            Err(Error::Overflow)
+        ));
+        header.partition_entry_lba = 2;
+        assert_eq!(header, expected);
+
+        // no usable sector left
+        let mut cur = io::Cursor::new(vec![0; 67 * 512]);
+        assert!(header.update_from(&mut cur, 512).is_err());
+        let mut cur = io::Cursor::new(vec![0; 68 * 512]);
+        assert!(header.update_from(&mut cur, 512).is_ok());
+        assert_eq!(header.first_usable_lba, header.last_usable_lba);
+    }
 
-        assert_eq!(gpt.find_first_place(10000), None);
-        assert_eq!(gpt.find_first_place(4), Some(44));
-        assert_eq!(gpt.find_first_place(8), Some(53));
+    #[test]
+    fn display_guid() {
+        let efi = partition_types::find_by_name("EFI System partition").unwrap();
+
+        assert_eq!(
+            GuidDisplay(&efi.guid).to_string(),
+            "c12a7328-f81f-11d2-ba4b-00a0c93ec93b"
+        );
+        assert_eq!(
+            format!("{:X}", GuidDisplay(&efi.guid)),
+            "C12A7328-F81F-11D2-BA4B-00A0C93EC93B"
+        );
     }
 
     #[test]
-    fn add_partition_left_aligned() {
-        let mut gpt = GPT::find_from(&mut fs::File::open(DISK1).unwrap()).unwrap();
+    fn parse_invalid_guid() {
+        for s in [
+            "",
+            "12880033-50d7-9e41-921c-1433db8d1f9",
+            "12880033-50d7-9e41-921c-1433db8d1f933",
+            "12880033-50d7-9e41-921c+1433db8d1f93",
+            "1288003350d7-9e41-921c-1433db8d1f93-",
+            "12880033-50d7-9e41-921c-1433db8d1g93",
+            "12880033-50d7-9e41-921c-1433db8d1f9é",
+        ] {
+            assert!(matches!(parse_guid(s), Err(Error::InvalidGuid(x)) if x == s));
+        }
 
-        gpt.align = 10000;
-        assert_eq!(gpt.find_first_place(1), None);
-        gpt.align = 4;
-        assert_eq!(gpt.find_first_place(4), Some(44));
-        gpt.align = 6;
-        assert_eq!(gpt.find_first_place(4), Some(54));
+        let guid = parse_guid("C12A7328-F81F-11D2-BA4B-00A0C93EC93B").unwrap();
+        assert_eq!(
+            partition_types::find_by_guid(&guid).map(|x| x.name),
+            Some("EFI System partition")
+        );
+        assert_eq!(parse_guid(&GuidDisplay(&guid).to_string()).ok(), Some(guid));
     }
 
     #[test]
-    fn add_partition_right() {
-        let mut gpt = GPT::find_from(&mut fs::File::open(DISK2).unwrap()).unwrap();
-        gpt.align = 1;
+    fn parse_entry_spec() {
+        let gpt = GPT::read_from(&mut fs::File::open(DISK2).unwrap(), 4096).unwrap();
+        let gpt = gpt.with_align(1).unwrap();
+
+        let entry = gpt
+            .parse_entry_spec(
+                "type=EFI System partition,start=16KiB,size=5,name=EFI,attr=0x4,\
+                 uuid=12880033-50d7-9e41-921c-1433db8d1f93",
+            )
+            .unwrap();
+        assert_eq!(
+            entry.partition_type_guid,
+            partition_types::find_by_alias("esp").unwrap().guid
+        );
+        assert_eq!(entry.starting_lba, 4);
+        assert_eq!(entry.ending_lba, 8);
+        assert_eq!(entry.partition_name.as_str(), "EFI");
+        assert_eq!(entry.attribute_bits, 4);
+        assert_eq!(
+            GuidDisplay(&entry.unique_partition_guid).to_string(),
+            "12880033-50d7-9e41-921c-1433db8d1f93"
+        );
 
-        assert_eq!(gpt.find_last_place(10000), None);
-        assert_eq!(gpt.find_last_place(5), Some(90));
-        assert_eq!(gpt.find_last_place(20), Some(50));
+        let entry = gpt
+            .parse_entry_spec("type=0FC63DAF-8483-4772-8E79-3D69D8477DE4,size=4097B,attr=2")
+            .unwrap();
+        assert_eq!(entry.size().ok(), Some(2));
+        assert_eq!(entry.attribute_bits, 2);
+        assert_eq!(entry.unique_partition_guid, [0; 16]);
+        assert_eq!(Some(entry.starting_lba), gpt.find_first_place(2));
+
+        let entry = gpt
+            .parse_entry_spec(" type = linux , start = 54 , size = 2 , name = boot , attr = 0x1 ")
+            .unwrap();
+        assert_eq!(entry.range().ok(), Some(54..=55));
+        assert_eq!(entry.partition_name.as_str(), "boot");
+        assert_eq!(entry.attribute_bits, 1);
+
+        let (start, len) = gpt.find_free_sectors()[0];
+        let entry = gpt.parse_entry_spec("type=linux").unwrap();
+        assert_eq!(entry.range().ok(), Some(start..=start + len - 1));
+
+        for spec in [
+            "",
+            "start=10",
+            "type=linux,size=0",
+            "type=linux,size=1PiB",
+            "type=linux,start=-1",
+            "type=linux,attr=foo",
+            "type=linux,foo=bar",
+            "type=linux,name",
+        ] {
+            assert!(matches!(
+                gpt.parse_entry_spec(spec),
+                Err(Error::InvalidEntrySpec(_))
+            ));
+        }
+        assert!(matches!(
+            gpt.parse_entry_spec("type=foo"),
+            Err(Error::UnknownPartitionType(_))
+        ));
+        assert!(matches!(
+            gpt.parse_entry_spec("type=linux,uuid=foo"),
+            Err(Error::InvalidGuid(_))
+        ));
+        assert!(matches!(
+            gpt.parse_entry_spec("type=linux,size=1TiB"),
+            Err(Error::NoSpaceLeft)
+        ));
     }
 
     #[test]
-    fn add_partition_right_aligned() {
-        let mut gpt = GPT::find_from(&mut fs::File::open(DISK2).unwrap()).unwrap();
+    fn read_partition_name_with_garbage_after_nul() {
+        let entry = GPTPartitionEntry {
+            partition_type_guid: [0xff; 16],
+            unique_partition_guid: [0xee; 16],
+            starting_lba: 34,
+            ending_lba: 43,
+            attribute_bits: 0,
+            partition_name: "EFI ".into(),
+        };
+        let mut data = serialize(&entry).unwrap();
+        // NOTE: the name starts at the byte 56, "EFI " is followed by a null code unit
+        for (i, x) in data[56 + 10..].iter_mut().enumerate() {
+            *x = b'A' + i as u8 % 26;
+        }
+        data.extend(serialize(&entry).unwrap());
 
-        gpt.align = 10000;
-        assert_eq!(gpt.find_last_place(1), None);
-        gpt.align = 4;
-        assert_eq!(gpt.find_last_place(5), Some(88));
-        gpt.align = 8;
-        assert_eq!(gpt.find_last_place(20), Some(48));
+        let mut cur = io::Cursor::new(data);
+        let read = GPTPartitionEntry::read_from(&mut cur).unwrap();
+        assert_eq!(read.partition_name.as_str(), "EFI ");
+        assert_eq!(read, entry);
+        assert_eq!(GPTPartitionEntry::read_from(&mut cur).unwrap(), entry);
 
-        // NOTE: special case where there is just enough space but it's not aligned
-        gpt.align = 1;
-        assert_eq!(gpt.find_last_place(54), Some(16));
-        assert_eq!(gpt.find_last_place(55), None);
-        gpt.align = 10;
-        assert_eq!(gpt.find_last_place(54), None);
+        let written = serialize(&read).unwrap();
+        assert!(written[56 + 8..].iter().all(|x| *x == 0));
     }
 
     #[test]
-    fn add_partition_optimal() {
-        let mut gpt = GPT::find_from(&mut fs::File::open(DISK2).unwrap()).unwrap();
-        gpt.align = 1;
+    fn hash_partition_entries() {
+        let gpt = GPT::find_from(&mut fs::File::open(DISK1).unwrap()).unwrap();
+
+        let entries = gpt.iter().map(|(_, x)| x).collect::<HashSet<_>>();
+        assert_eq!(entries.len(), 3);
+        assert!(entries.contains(&GPTPartitionEntry::empty()));
+        assert!(entries.contains(&gpt[1]));
+        assert!(entries.contains(&gpt[2]));
+    }
 
-        assert_eq!(gpt.find_optimal_place(10000), None);
-        assert_eq!(gpt.find_optimal_place(5), Some(80));
-        assert_eq!(gpt.find_optimal_place(20), Some(16));
+    #[test]
+    fn set_partition_fields() {
+        let mut gpt = GPT::find_from(&mut fs::File::open(DISK1).unwrap()).unwrap();
+        let n = gpt.header.number_of_partition_entries;
+
+        for i in [0, n + 1] {
+            assert!(gpt.set_partition_name(i, "Foo").is_err());
+            assert!(gpt.set_partition_type_guid(i, [1; 16]).is_err());
+            assert!(gpt.set_unique_partition_guid(i, [1; 16]).is_err());
+            assert!(gpt.set_attribute_bits(i, 1).is_err());
+        }
+        assert!(matches!(
+            gpt.set_partition_name(1, &"\u{1f916}".repeat(19)),
+            Err(Error::InvalidPartitionName(_))
+        ));
+        assert!(matches!(
+            gpt.set_partition_name(1, "Foo\0"),
+            Err(Error::InvalidPartitionName(_))
+        ));
+        gpt.set_partition_name(1, &"\u{1f916}".repeat(18)).unwrap();
+        let guid = gpt[2].unique_partition_guid;
+        assert!(matches!(
+            gpt.set_unique_partition_guid(1, guid),
+            Err(Error::ConflictPartitionGUID)
+        ));
+        gpt.set_unique_partition_guid(2, guid).unwrap();
+        gpt.set_unique_partition_guid(1, [3; 16]).unwrap();
+        gpt.set_partition_type_guid(1, [4; 16]).unwrap();
+        gpt.set_attribute_bits(1, 5).unwrap();
+
+        assert_eq!(gpt[1].partition_name.as_str(), "\u{1f916}".repeat(18));
+        assert_eq!(gpt[1].unique_partition_guid, [3; 16]);
+        assert_eq!(gpt[1].partition_type_guid, [4; 16]);
+        assert_eq!(gpt[1].attribute_bits, 5);
     }
 
     #[test]
-    fn add_partition_optimal_aligned() {
-        let mut gpt = GPT::find_from(&mut fs::File::open(DISK2).unwrap()).unwrap();
+    fn set_partition() {
+        let mut gpt = GPT::read_from(&mut fs::File::open(DISK1).unwrap(), 512).unwrap();
+        let entry = GPTPartitionEntry {
+            unique_partition_guid: [0xee; 16],
+            starting_lba: 54,
+            ending_lba: 60,
+            ..gpt[1].clone()
+        };
 
-        gpt.align = 10000;
-        assert_eq!(gpt.find_optimal_place(1), None);
-        gpt.align = 6;
-        assert_eq!(gpt.find_optimal_place(5), Some(84));
-        gpt.align = 9;
-        assert_eq!(gpt.find_optimal_place(20), Some(18));
+        assert!(matches!(
+            gpt.set_partition(0, entry.clone()),
+            Err(Error::InvalidPartitionNumber(0))
+        ));
+        for (starting_lba, ending_lba) in [(33, 40), (60, 67), (52, 60), (60, 54)] {
+            assert!(matches!(
+                gpt.set_partition(
+                    3,
+                    GPTPartitionEntry {
+                        starting_lba,
+                        ending_lba,
+                        ..entry.clone()
+                    }
+                ),
+                Err(Error::InvalidPartitionBoundaries)
+            ));
+        }
+        assert!(matches!(
+            gpt.set_partition(
+                3,
+                GPTPartitionEntry {
+                    unique_partition_guid: gpt[2].unique_partition_guid,
+                    ..entry.clone()
+                }
+            ),
+            Err(Error::ConflictPartitionGUID)
+        ));
+        assert!(gpt[3].is_unused());
+
+        // replacing a partition by itself
+        let bar = gpt[2].clone();
+        gpt.set_partition(2, bar).unwrap();
+        gpt.set_partition(3, entry.clone()).unwrap();
+        assert_eq!(gpt[3], entry);
+        gpt.set_partition(3, GPTPartitionEntry::empty()).unwrap();
+        assert!(gpt[3].is_unused());
     }
 
     #[test]
@@ -1555,6 +6248,31 @@ mod test {
         );
     }
 
+    #[test]
+    fn normalize_sparse_table() {
+        let mut cur = io::Cursor::new(fs::read(DISK1).unwrap());
+        let mut gpt = GPT::read_from(&mut cur, 512).unwrap();
+        let foo = gpt[1].clone();
+        let bar = gpt[2].clone();
+        gpt[1] = GPTPartitionEntry::empty();
+        gpt[2] = GPTPartitionEntry {
+            partition_name: "stale".into(),
+            ..GPTPartitionEntry::empty()
+        };
+        gpt[5] = bar.clone();
+        gpt[128] = foo.clone();
+
+        gpt.normalize();
+
+        assert_eq!(gpt[1], foo);
+        assert_eq!(gpt[2], bar);
+        assert!(gpt
+            .iter()
+            .skip(2)
+            .all(|(_, x)| *x == GPTPartitionEntry::empty()));
+        gpt.write_into(&mut cur).unwrap();
+    }
+
     #[test]
     fn add_partition_on_unsorted_table() {
         let mut gpt = GPT::find_from(&mut fs::File::open(DISK1).unwrap()).unwrap();
@@ -1635,6 +6353,205 @@ mod test {
             assert_eq!(gpt.header.primary_lba, backup_lba);
             assert_eq!(gpt.header.backup_lba, 1);
             assert_eq!(gpt.header.partition_entry_lba, partition_entry_lba);
+
+            assert_eq!(gpt.primary_header_byte_offset().unwrap(), ss);
+            assert_eq!(gpt.backup_header_byte_offset().unwrap(), backup_lba * ss);
+
+            let mut gpt = gpt;
+            let headers = gpt.write_headers_into(&mut cur).unwrap();
+            assert_eq!(headers.backup, gpt.header);
+            assert_eq!(headers.primary, primary_header);
+            assert_eq!(headers.primary.primary_lba, 1);
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn write_zeroes_the_end_of_the_header_sectors() {
+        fn test(path: &str, ss: u64) {
+            let mut cur = io::Cursor::new(fs::read(path).unwrap());
+            let mut gpt = GPT::read_from(&mut cur, ss).unwrap();
+            let len = cur.get_ref().len() as u64;
+
+            for lba in [1, len / ss - 1] {
+                let start = (lba * ss) as usize;
+                cur.get_mut()[start + 92..start + ss as usize].fill(0xff);
+            }
+            gpt.write_into(&mut cur).unwrap();
+
+            for lba in [1, len / ss - 1] {
+                let start = (lba * ss) as usize;
+                assert!(cur.get_ref()[start + 92..start + ss as usize]
+                    .iter()
+                    .all(|x| *x == 0));
+            }
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn write_backup_first() {
+        #[derive(Debug, PartialEq)]
+        enum Event {
+            Write(u64),
+            Flush,
+        }
+
+        struct Recorder {
+            cur: io::Cursor<Vec<u8>>,
+            events: Vec<Event>,
+        }
+
+        impl Write for Recorder {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.events.push(Event::Write(self.cur.position()));
+                self.cur.write(buf)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                self.events.push(Event::Flush);
+                Ok(())
+            }
+        }
+
+        impl Seek for Recorder {
+            fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+                self.cur.seek(pos)
+            }
+        }
+
+        fn test(path: &str, ss: u64, use_backup: bool, shrink: bool) {
+            let mut cur = io::Cursor::new(fs::read(path).unwrap());
+            if use_backup {
+                cur.get_mut()[ss as usize] ^= 0xff;
+            }
+            let mut gpt = GPT::read_from(&mut cur, ss).unwrap();
+            assert_eq!(gpt.header.is_backup(), use_backup);
+            let old_primary_array = gpt.primary_partition_entry_lba() * ss
+                ..(gpt.primary_partition_entry_lba() + gpt.partition_array_sectors()) * ss;
+            if shrink {
+                gpt.set_number_of_partition_entries(16).unwrap();
+            }
+            let backup_start = gpt.backup_partition_entry_lba() * ss;
+
+            let mut recorder = Recorder {
+                cur,
+                events: Vec::new(),
+            };
+            let headers = gpt.write_backup_first_into(&mut recorder).unwrap();
+
+            let first_flush = recorder
+                .events
+                .iter()
+                .position(|x| *x == Event::Flush)
+                .unwrap();
+            assert!(first_flush > 0);
+            assert!(recorder.events[..first_flush]
+                .iter()
+                .all(|x| matches!(x, Event::Write(pos) if *pos >= backup_start)));
+            // NOTE: the old primary partition entry array is left untouched until the backup copy
+            //       is complete
+            assert!(!recorder.events[..first_flush]
+                .iter()
+                .any(|x| matches!(x, Event::Write(pos) if old_primary_array.contains(pos))));
+            assert!(recorder.events[first_flush + 1..]
+                .iter()
+                .take_while(|x| **x != Event::Flush)
+                .all(|x| matches!(x, Event::Write(pos) if *pos < backup_start)));
+            assert_eq!(recorder.events.last(), Some(&Event::Flush));
+
+            let mut cur = recorder.cur;
+            let primary = GPT::read_from(&mut cur, ss).unwrap();
+            assert_eq!(primary.header, headers.primary);
+            cur.get_mut()[ss as usize] ^= 0xff;
+            let backup = GPT::read_from(&mut cur, ss).unwrap();
+            assert_eq!(backup.header, headers.backup);
+        }
+
+        for shrink in [false, true] {
+            test(DISK1, 512, false, shrink);
+            test(DISK1, 512, true, shrink);
+            test(DISK2, 4096, false, shrink);
+            test(DISK2, 4096, true, shrink);
+        }
+    }
+
+    #[test]
+    fn write_to_an_empty_writer() {
+        fn test(path: &str, ss: u64) {
+            let data = fs::read(path).unwrap();
+            let mut gpt = GPT::read_from(&mut io::Cursor::new(data.clone()), ss).unwrap();
+
+            // NOTE: an empty writer is a disk of length 0
+            let mut cur = io::Cursor::new(Vec::new());
+            match gpt.write_into(&mut cur) {
+                Err(Error::DiskTooSmall { required, found }) => {
+                    assert_eq!(required, data.len() as u64);
+                    assert_eq!(found, 0);
+                }
+                x => panic!("unexpected result: {:?}", x),
+            }
+            assert!(cur.get_ref().is_empty());
+
+            // NOTE: the writer grows, like a new file, if the length is given
+            gpt.write_into_with_len(&mut cur, data.len() as u64)
+                .unwrap();
+            assert_eq!(cur.get_ref().len(), data.len());
+            assert_eq!(GPT::read_from(&mut cur, ss).unwrap(), gpt);
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn write_with_len() {
+        fn test(path: &str, ss: u64) {
+            let data = fs::read(path).unwrap();
+            let len = data.len() as u64;
+            let mut gpt = GPT::read_from(&mut io::Cursor::new(data), ss).unwrap();
+
+            let mut cur = io::Cursor::new(Vec::new());
+            match gpt.write_into_with_len(&mut cur, len - 1) {
+                Err(Error::DiskTooSmall { required, found }) => {
+                    assert_eq!(required, len);
+                    assert_eq!(found, len - 1);
+                }
+                x => panic!("unexpected result: {:?}", x),
+            }
+            assert!(cur.get_ref().is_empty());
+
+            // NOTE: the length given is used even if seeking to the end returns something else
+            let backup = gpt.write_into_with_len(&mut cur, len).unwrap();
+            assert!(backup.is_backup());
+            assert_eq!(cur.get_ref().len() as u64, len);
+            assert_eq!(GPT::read_from(&mut cur, ss).unwrap(), gpt);
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn write_to_a_smaller_disk() {
+        fn test(path: &str, ss: u64) {
+            let data = fs::read(path).unwrap();
+            let len = data.len() as u64;
+            let mut gpt = GPT::read_from(&mut io::Cursor::new(data), ss).unwrap();
+
+            let mut cur = io::Cursor::new(vec![0; (len - ss) as usize]);
+            match gpt.write_into(&mut cur) {
+                Err(Error::DiskTooSmall { required, found }) => {
+                    assert_eq!(required, len);
+                    assert_eq!(found, len - ss);
+                }
+                x => panic!("unexpected result: {:?}", x),
+            }
+            assert!(cur.get_ref().iter().all(|x| *x == 0));
         }
 
         test(DISK1, 512);
@@ -1808,6 +6725,59 @@ mod test {
         test(4096, 2048 * 4);
     }
 
+    #[test]
+    fn find_alignment_matches_linear_search() {
+        fn linear_search(lbas: &[u64]) -> u64 {
+            (1..=MAX_ALIGN.min(*lbas.iter().max().unwrap()))
+                .filter(|div| lbas.iter().all(|x| x % div == 0))
+                .max()
+                .unwrap()
+        }
+
+        let mut rng = Rng(0x1234_5678_9abc_def0);
+        let header =
+            GPTHeader::new_from(&mut io::Cursor::new(vec![0; 100 * 512]), 512, [0; 16]).unwrap();
+        for lbas in [
+            vec![2048, 4096],
+            vec![34, 2048],
+            vec![20000],
+            vec![20000, 40000],
+            vec![32768, 65536],
+            vec![3 * 16384 * 7],
+        ] {
+            let partitions = lbas
+                .iter()
+                .map(|x| GPTPartitionEntry {
+                    partition_type_guid: [0xff; 16],
+                    starting_lba: *x,
+                    ..GPTPartitionEntry::empty()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                GPT::find_alignment(&header, &partitions),
+                linear_search(&lbas)
+            );
+        }
+        for _ in 0..1000 {
+            let align = 1 << rng.below(16);
+            let lbas = (0..1 + rng.below(4))
+                .map(|_| (1 + rng.below(1000)) * align * (1 + rng.below(3)))
+                .collect::<Vec<_>>();
+            let partitions = lbas
+                .iter()
+                .map(|x| GPTPartitionEntry {
+                    partition_type_guid: [0xff; 16],
+                    starting_lba: *x,
+                    ..GPTPartitionEntry::empty()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                GPT::find_alignment(&header, &partitions),
+                linear_search(&lbas)
+            );
+        }
+    }
+
     #[test]
     fn determine_partition_alignment_full_disk() {
         fn test(ss: u64) {
@@ -1872,6 +6842,264 @@ mod test {
         test(4096);
     }
 
+    #[test]
+    fn ensure_protective_mbr() {
+        fn test(path: &str, ss: u64) {
+            let data = fs::read(path).unwrap();
+            let mut cur = io::Cursor::new(data.clone());
+            assert!(!GPT::ensure_protective_mbr(&mut cur, ss).unwrap());
+            assert_eq!(cur.get_ref(), &data);
+
+            // NOTE: an MBR without partition of type 0xee is not a protective MBR
+            cur.seek(SeekFrom::Start(446 + 4)).unwrap();
+            cur.write_all(&[0x83]).unwrap();
+            assert!(GPT::ensure_protective_mbr(&mut cur, ss).unwrap());
+            assert_eq!(cur.get_ref()[446 + 4], 0xee);
+            assert!(!GPT::ensure_protective_mbr(&mut cur, ss).unwrap());
+
+            // NOTE: a partition of type 0xee which doesn't start at the LBA 1 is not a protective
+            //       MBR either
+            let disk_sectors = data.len() as u64 / ss;
+            cur.seek(SeekFrom::Start(446 + 8)).unwrap();
+            cur.write_all(&2_u32.to_le_bytes()).unwrap();
+            assert!(GPT::ensure_protective_mbr(&mut cur, ss).unwrap());
+            assert_eq!(
+                cur.get_ref()[446..512],
+                GPT::protective_mbr_bytes(disk_sectors)[446..]
+            );
+
+            // NOTE: a hybrid MBR is kept as it is
+            let mut hybrid = GPT::protective_mbr_bytes(disk_sectors);
+            hybrid[446 + 12..446 + 16].copy_from_slice(&33_u32.to_le_bytes());
+            hybrid[462..478].copy_from_slice(&[
+                0x80, 0xfe, 0xff, 0xff, 0x0c, 0xfe, 0xff, 0xff, 34, 0, 0, 0, 10, 0, 0, 0,
+            ]);
+            cur.get_mut()[..512].copy_from_slice(&hybrid);
+            assert!(!GPT::ensure_protective_mbr(&mut cur, ss).unwrap());
+            assert_eq!(cur.get_ref()[..512], hybrid);
+
+            assert!(matches!(
+                GPT::ensure_protective_mbr(&mut cur, 0),
+                Err(Error::InvalidSectorSize(0))
+            ));
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn protective_mbr_bytes() {
+        let mbr = GPT::protective_mbr_bytes(100);
+        assert!(mbr[..446].iter().all(|x| *x == 0));
+        assert_eq!(mbr[446], 0x00);
+        assert_eq!(mbr[446 + 4], 0xee);
+        assert_eq!(mbr[446 + 8..446 + 12], 1_u32.to_le_bytes());
+        assert_eq!(mbr[446 + 12..446 + 16], 99_u32.to_le_bytes());
+        assert!(mbr[462..510].iter().all(|x| *x == 0));
+        assert_eq!(mbr[510..], [0x55, 0xaa]);
+
+        let mbr = GPT::bootable_protective_mbr_bytes(u64::MAX);
+        assert_eq!(mbr[446], 0x80);
+        assert_eq!(mbr[446 + 12..446 + 16], u32::MAX.to_le_bytes());
+    }
+
+    /// Small deterministic pseudo-random number generator (xorshift64) for the round-trip tests.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next_u64(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: u64) -> u64 {
+            self.next_u64() % n
+        }
+
+        fn guid(&mut self) -> [u8; 16] {
+            let mut guid = [0; 16];
+            guid[..8].copy_from_slice(&self.next_u64().to_le_bytes());
+            guid[8..].copy_from_slice(&self.next_u64().to_le_bytes());
+            guid
+        }
+
+        fn partition_name(&mut self) -> PartitionName {
+            const CHARS: &[char] = &['a', 'Z', '0', ' ', '-', 'é', 'ß', 'ж', '日', '🦀', '𝄞'];
+            let mut name = String::new();
+            loop {
+                let c = CHARS[self.below(CHARS.len() as u64) as usize];
+                if name.encode_utf16().count() + c.len_utf16() > PartitionName::MAX_UTF16_LEN
+                    || self.below(8) == 0
+                {
+                    break;
+                }
+                name.push(c);
+            }
+            PartitionName::new(&name).unwrap()
+        }
+
+        fn partition_entry(&mut self) -> GPTPartitionEntry {
+            GPTPartitionEntry {
+                partition_type_guid: self.guid(),
+                unique_partition_guid: self.guid(),
+                starting_lba: self.next_u64(),
+                ending_lba: self.next_u64(),
+                attribute_bits: self.next_u64(),
+                partition_name: self.partition_name(),
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip_random_headers_and_partition_entries() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+
+        for _ in 0..1000 {
+            // NOTE: the partition entry array must not be bigger than 128 MiB
+            let shift = rng.below(8);
+            let mut header = GPTHeader {
+                signature: *b"EFI PART",
+                revision: [0x00, 0x00, 0x01, 0x00],
+                header_size: 92,
+                crc32_checksum: 0,
+                reserved: [0; 4],
+                primary_lba: rng.next_u64(),
+                backup_lba: rng.next_u64(),
+                first_usable_lba: rng.next_u64(),
+                last_usable_lba: rng.next_u64(),
+                disk_guid: rng.guid(),
+                partition_entry_lba: rng.next_u64(),
+                number_of_partition_entries: rng
+                    .below(u64::from(MAX_PARTITION_ENTRIES >> shift) + 1)
+                    as u32,
+                size_of_partition_entry: 128 << shift,
+                partition_entry_array_crc32: rng.next_u64() as u32,
+            };
+            header.crc32_checksum = header.generate_crc32_checksum();
+            let mut data = Vec::new();
+            serialize_into(&mut data, &header).unwrap();
+            assert_eq!(data.len(), 92);
+            let mut cur = io::Cursor::new(data);
+            assert_eq!(GPTHeader::read_from(&mut cur).unwrap(), header);
+
+            let entry = rng.partition_entry();
+            let mut data = Vec::new();
+            serialize_into(&mut data, &entry).unwrap();
+            assert_eq!(data.len(), 128);
+            assert_eq!(
+                GPTPartitionEntry::read_from(&mut data.as_slice()).unwrap(),
+                entry
+            );
+        }
+    }
+
+    #[test]
+    fn round_trip_random_gpts() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..100 {
+            let ss = if rng.below(2) == 0 { 512 } else { 4096 };
+            let sectors = 64 + rng.below(512);
+            let mut cur = io::Cursor::new(vec![0; (sectors * ss) as usize]);
+            let mut gpt = GPT::new_from(&mut cur, ss, rng.guid()).unwrap();
+            gpt.set_number_of_partition_entries(1 + rng.below(128) as u32)
+                .unwrap();
+
+            for i in 1..=gpt.header.number_of_partition_entries {
+                let free = gpt.find_free_sectors();
+                if free.is_empty() || rng.below(4) == 0 {
+                    break;
+                }
+                let (start, len) = free[rng.below(free.len() as u64) as usize];
+                let offset = rng.below(len);
+                let size = 1 + rng.below(len - offset);
+                gpt[i] = GPTPartitionEntry {
+                    starting_lba: start + offset,
+                    ending_lba: start + offset + size - 1,
+                    ..rng.partition_entry()
+                };
+            }
+
+            gpt.write_into(&mut cur).unwrap();
+            let read = GPT::read_from(&mut cur, ss).unwrap();
+            assert_eq!(read.header, gpt.header);
+            assert!(read.iter().eq(gpt.iter()));
+        }
+    }
+
+    #[test]
+    fn write_to_image() {
+        fn test(path: &str, ss: u64) {
+            let mut f = fs::File::open(path).unwrap();
+            let mut gpt = GPT::read_from(&mut f, ss).unwrap();
+            let partitions: Vec<_> = gpt.iter().map(|(_, x)| x.clone()).collect();
+
+            assert!(gpt.write_to_image(3, &mut Vec::new()).is_err());
+
+            let mut image = Vec::new();
+            gpt.write_to_image(200, &mut image).unwrap();
+            assert_eq!(image.len() as u64, 200 * ss);
+            assert_eq!(image[510..512], [0x55, 0xaa]);
+
+            let mut cur = io::Cursor::new(image);
+            let gpt = GPT::find_from(&mut cur).unwrap();
+            assert_eq!(gpt.sector_size(), ss);
+            assert_eq!(gpt.header.backup_lba, 199);
+            assert!(gpt.iter().map(|(_, x)| x).eq(partitions.iter()));
+        }
+
+        test(DISK1, 512);
+        test(DISK2, 4096);
+    }
+
+    #[test]
+    fn copy_data_between() {
+        let len = 5 * 1024 * 1024 / 2;
+        let src: Vec<u8> = (0..len).map(|x| x as u8).collect();
+        let mut dst = io::Cursor::new(vec![0; len + 512]);
+        let mut progress = Vec::new();
+
+        super::copy_data_between(
+            &mut io::Cursor::new(&src),
+            0..=len as u64 - 1,
+            &mut dst,
+            512..=len as u64 + 511,
+            |copied, total| progress.push((copied, total)),
+        )
+        .unwrap();
+        assert_eq!(dst.get_ref()[512..], src[..]);
+        assert_eq!(
+            progress,
+            [
+                (1024 * 1024, len as u64),
+                (2 * 1024 * 1024, len as u64),
+                (len as u64, len as u64)
+            ]
+        );
+
+        assert!(matches!(
+            super::copy_data_between(
+                &mut io::Cursor::new(&src),
+                0..=1023,
+                &mut dst,
+                0..=1022,
+                |_, _| {}
+            ),
+            Err(Error::NoSpaceLeft)
+        ));
+        assert!(super::copy_data_between(
+            &mut io::Cursor::new(&src),
+            0..=len as u64,
+            &mut dst,
+            0..=len as u64,
+            |_, _| {}
+        )
+        .is_err());
+    }
+
     #[test]
     fn read_from_smaller_disk_and_write_to_bigger_disk() {
         fn test(path: &str, ss: u64) {
@@ -4,6 +4,7 @@ use std::os::linux::fs::MetadataExt;
 use std::os::unix::io::AsRawFd;
 use thiserror::Error;
 
+#[allow(clippy::incompatible_msrv)]
 mod ioctl {
     use nix::{ioctl_none, ioctl_read_bad};
 
@@ -0,0 +1,419 @@
+use crate::{Error, Result, GUID_TEXT_POSITIONS};
+
+/// A well-known partition type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartitionType {
+    /// The operating system or vendor the partition type belongs to.
+    pub category: &'static str,
+    /// The human readable name of the partition type.
+    pub name: &'static str,
+    /// 16 bytes representing the UUID of the partition's type (as stored on disk).
+    pub guid: [u8; 16],
+}
+
+/// Converts a GUID in its textual form (`XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX`) to the mixed-endian
+/// representation used in the GPT.
+const fn guid(s: &str) -> [u8; 16] {
+    const fn hex(c: u8) -> u8 {
+        match c {
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' => c - b'a' + 10,
+            b'A'..=b'F' => c - b'A' + 10,
+            _ => panic!("invalid hexadecimal digit in GUID"),
+        }
+    }
+    let s = s.as_bytes();
+    let mut bytes = [0; 16];
+    let mut i = 0;
+    while i < 16 {
+        bytes[i] = hex(s[GUID_TEXT_POSITIONS[i]]) << 4 | hex(s[GUID_TEXT_POSITIONS[i] + 1]);
+        i += 1;
+    }
+
+    bytes
+}
+
+macro_rules! partition_types {
+    ($($category:literal => { $($name:literal = $guid:literal,)* })*) => {
+        /// The list of the well-known partition types.
+        ///
+        /// See [Wikipedia's page](https://en.wikipedia.org/wiki/GUID_Partition_Table#Partition_type_GUIDs)
+        /// for more information.
+        pub const PARTITION_TYPES: &[PartitionType] = &[
+            $($(PartitionType { category: $category, name: $name, guid: guid($guid) },)*)*
+        ];
+
+        /// The list of the categories of the well-known partition types (in the same order as
+        /// [`PARTITION_TYPES`]).
+        pub const CATEGORIES: &[&str] = &[$($category,)*];
+    };
+}
+
+partition_types! {
+    "None" => {
+        "MBR partition scheme" = "024DEE41-33E7-11D3-9D69-0008C781F39F",
+        "EFI System partition" = "C12A7328-F81F-11D2-BA4B-00A0C93EC93B",
+        "BIOS boot partition" = "21686148-6449-6E6F-744E-656564454649",
+        "Intel Fast Flash (iFFS) partition" = "D3BFE2DE-3DAF-11DF-BA40-E3A556D89593",
+        "Sony boot partition" = "F4019732-066E-4E12-8273-346C5641494F",
+        "Lenovo boot partition" = "BFBFAFE7-A34F-448A-9A5B-6213EB736C22",
+    }
+    "Windows" => {
+        "Microsoft Reserved Partition" = "E3C9E316-0B5C-4DB8-817D-F92DF00215AE",
+        "Basic data partition" = "EBD0A0A2-B9E5-4433-87C0-68B6B72699C7",
+        "Logical Disk Manager metadata partition" = "5808C8AA-7E8F-42E0-85D2-E1E90434CFB3",
+        "Logical Disk Manager data partition" = "AF9B60A0-1431-4F62-BC68-3311714A69AD",
+        "Windows Recovery Environment" = "DE94BBA4-06D1-4D40-A16A-BFD50179D6AC",
+        "IBM General Parallel File System partition" = "37AFFC90-EF7D-4E96-91C3-2D7AE055B174",
+        "Storage Spaces partition" = "E75CAF8F-F680-4CEE-AFA3-B001E56EFC2D",
+        "Storage Replica partition" = "558D43C5-A1AC-43C0-AAC8-D1472B2923D1",
+    }
+    "HP-UX" => {
+        "Data partition" = "75894C1E-3AEB-11D3-B7C1-7B03A0000000",
+        "Service partition" = "E2A1E728-32E3-11D6-A682-7B03A0000000",
+    }
+    "Linux" => {
+        "Linux filesystem data" = "0FC63DAF-8483-4772-8E79-3D69D8477DE4",
+        "RAID partition" = "A19D880F-05FC-4D3B-A006-743F0F84911E",
+        "Root partition (x86)" = "44479540-F297-41B2-9AF7-D131D5F0458A",
+        "Root partition (x86-64)" = "4F68BCE3-E8CD-4DB1-96E7-FBCAF984B709",
+        "Root partition (32-bit ARM)" = "69DAD710-2CE4-4E3C-B16C-21A1D49ABED3",
+        "Root partition (64-bit ARM/AArch64)" = "B921B045-1DF0-41C3-AF44-4C6F280D3FAE",
+        "/boot partition" = "BC13C2FF-59E6-4262-A352-B275FD6F7172",
+        "Swap partition" = "0657FD6D-A4AB-43C4-84E5-0933C84B4F4F",
+        "Logical Volume Manager (LVM) partition" = "E6D6D379-F507-44C2-A23C-238F2A3DF928",
+        "/home partition" = "933AC7E1-2EB4-4F13-B844-0E14E2AEF915",
+        "/srv (server data) partition" = "3B8F8425-20E0-4F3B-907F-1A25A76F98E8",
+        "Plain dm-crypt partition" = "7FFEC5C9-2D00-49B7-8941-3EA10A5586B7",
+        "LUKS partition" = "CA7D7CCB-63ED-4C53-861C-1742536059CC",
+        "Reserved" = "8DA63339-0007-60C0-C436-083AC8230908",
+    }
+    "FreeBSD" => {
+        "Boot partition" = "83BD6B9D-7F41-11DC-BE0B-001560B84F0F",
+        "BSD disklabel partition" = "516E7CB4-6ECF-11D6-8FF8-00022D09712B",
+        "Swap partition" = "516E7CB5-6ECF-11D6-8FF8-00022D09712B",
+        "Unix File System (UFS) partition" = "516E7CB6-6ECF-11D6-8FF8-00022D09712B",
+        "Vinum volume manager partition" = "516E7CB8-6ECF-11D6-8FF8-00022D09712B",
+        "ZFS partition" = "516E7CBA-6ECF-11D6-8FF8-00022D09712B",
+        "nandfs partition" = "74BA7DD9-A689-11E1-BD04-00E081286ACF",
+    }
+    "macOS Darwin" => {
+        "Hierarchical File System Plus (HFS+) partition" = "48465300-0000-11AA-AA11-00306543ECAC",
+        "Apple APFS container" = "7C3457EF-0000-11AA-AA11-00306543ECAC",
+        "Apple UFS container" = "55465300-0000-11AA-AA11-00306543ECAC",
+        "Apple RAID partition" = "52414944-0000-11AA-AA11-00306543ECAC",
+        "Apple RAID partition, offline" = "52414944-5F4F-11AA-AA11-00306543ECAC",
+        "Apple Boot partition (Recovery HD)" = "426F6F74-0000-11AA-AA11-00306543ECAC",
+        "Apple Label" = "4C616265-6C00-11AA-AA11-00306543ECAC",
+        "Apple TV Recovery partition" = "5265636F-7665-11AA-AA11-00306543ECAC",
+        "Apple Core Storage Container" = "53746F72-6167-11AA-AA11-00306543ECAC",
+    }
+    "Solaris illumos" => {
+        "Boot partition" = "6A82CB45-1DD2-11B2-99A6-080020736631",
+        "Root partition" = "6A85CF4D-1DD2-11B2-99A6-080020736631",
+        "Swap partition" = "6A87C46F-1DD2-11B2-99A6-080020736631",
+        "Backup partition" = "6A8B642B-1DD2-11B2-99A6-080020736631",
+        "/usr partition" = "6A898CC3-1DD2-11B2-99A6-080020736631",
+        "/var partition" = "6A8EF2E9-1DD2-11B2-99A6-080020736631",
+        "/home partition" = "6A90BA39-1DD2-11B2-99A6-080020736631",
+        "Alternate sector" = "6A9283A5-1DD2-11B2-99A6-080020736631",
+        "Reserved partition" = "6A945A3B-1DD2-11B2-99A6-080020736631",
+    }
+    "NetBSD" => {
+        "Swap partition" = "49F48D32-B10E-11DC-B99B-0019D1879648",
+        "FFS partition" = "49F48D5A-B10E-11DC-B99B-0019D1879648",
+        "LFS partition" = "49F48D82-B10E-11DC-B99B-0019D1879648",
+        "RAID partition" = "49F48DAA-B10E-11DC-B99B-0019D1879648",
+        "Concatenated partition" = "2DB519C4-B10F-11DC-B99B-0019D1879648",
+        "Encrypted partition" = "2DB519EC-B10F-11DC-B99B-0019D1879648",
+    }
+    "ChromeOS" => {
+        "ChromeOS kernel" = "FE3A2A5D-4F32-41A7-B725-ACCC3285A309",
+        "ChromeOS rootfs" = "3CB8E202-3B7E-47DD-8A3C-7FF2A13CFCEC",
+        "ChromeOS future use" = "2E0A753D-9E48-43B0-8337-B15192CB1B5E",
+    }
+    "Haiku" => {
+        "Haiku BFS" = "42465331-3BA3-10F1-802A-4861696B7521",
+    }
+    "MidnightBSD" => {
+        "Boot partition" = "85D5E45E-237C-11E1-B4B3-E89A8F7FC3A7",
+        "Data partition" = "85D5E45A-237C-11E1-B4B3-E89A8F7FC3A7",
+        "Swap partition" = "85D5E45B-237C-11E1-B4B3-E89A8F7FC3A7",
+        "Unix File System (UFS) partition" = "0394EF8B-237E-11E1-B4B3-E89A8F7FC3A7",
+        "Vinum volume manager partition" = "85D5E45C-237C-11E1-B4B3-E89A8F7FC3A7",
+        "ZFS partition" = "85D5E45D-237C-11E1-B4B3-E89A8F7FC3A7",
+    }
+    "Ceph" => {
+        "Journal" = "45B0969E-9B03-4F30-B4C6-B4B80CEFF106",
+        "OSD" = "4FBD7E29-9D25-41B8-AFD0-062C0CEFF05D",
+    }
+    "OpenBSD" => {
+        "Data partition" = "824CC7A0-36A8-11E3-890A-952519AD3F61",
+    }
+    "QNX" => {
+        "Power-safe (QNX6) file system" = "CEF5A9AD-73BC-4601-89F3-CDEEEEE321A1",
+    }
+    "Plan 9" => {
+        "Plan 9 partition" = "C91818F9-8025-47AF-89D2-F030D7000C2C",
+    }
+    "VMware ESX" => {
+        "vmkcore (coredump partition)" = "9D275380-40AD-11DB-BF97-000C2911D1B8",
+        "VMFS filesystem partition" = "AA31E02A-400F-11DB-9590-000C2911D1B8",
+        "VMware Reserved" = "9198EFFC-31C0-11DB-8F78-000C2911D1B8",
+    }
+}
+
+/// Find a well-known partition type by its GUID.
+///
+/// # Examples
+///
+/// Basic usage:
+/// ```
+/// let mut f = std::fs::File::open("tests/fixtures/disk1.img")
+///     .expect("could not open disk");
+/// let gpt = gptman::GPT::find_from(&mut f)
+///     .expect("could not find GPT");
+///
+/// if let Some(t) = gptman::partition_types::find_by_guid(&gpt[1].partition_type_guid) {
+///     println!("Partition #1: {} ({})", t.name, t.category);
+/// }
+/// ```
+pub fn find_by_guid(guid: &[u8; 16]) -> Option<&'static PartitionType> {
+    PARTITION_TYPES.iter().find(|x| &x.guid == guid)
+}
+
+/// Find a well-known partition type by its name (case insensitive).
+///
+/// # Errors
+///
+/// This function will return an error if no partition type matches the name or if more than one
+/// partition type (from different categories) matches the name.
+///
+/// # Examples
+///
+/// Basic usage:
+/// ```
+/// let t = gptman::partition_types::find_by_name("efi system partition").unwrap();
+///
+/// assert_eq!(t.name, "EFI System partition");
+/// assert!(gptman::partition_types::find_by_name("Data partition").is_err());
+/// ```
+pub fn find_by_name(name: &str) -> Result<&'static PartitionType> {
+    let mut matches = PARTITION_TYPES
+        .iter()
+        .filter(|x| x.name.eq_ignore_ascii_case(name));
+
+    match (matches.next(), matches.next()) {
+        (Some(x), None) => Ok(x),
+        (Some(_), Some(_)) => Err(Error::AmbiguousPartitionType(name.to_string())),
+        (None, _) => Err(Error::UnknownPartitionType(name.to_string())),
+    }
+}
+
+/// Common names of filesystems and partition usages associated with the category and the name of
+/// their partition type.
+const ALIASES: &[(&str, &str, &str)] = &[
+    ("efi", "None", "EFI System partition"),
+    ("esp", "None", "EFI System partition"),
+    ("bios", "None", "BIOS boot partition"),
+    ("fat", "Windows", "Basic data partition"),
+    ("fat12", "Windows", "Basic data partition"),
+    ("fat16", "Windows", "Basic data partition"),
+    ("fat32", "Windows", "Basic data partition"),
+    ("vfat", "Windows", "Basic data partition"),
+    ("exfat", "Windows", "Basic data partition"),
+    ("ntfs", "Windows", "Basic data partition"),
+    ("linux", "Linux", "Linux filesystem data"),
+    ("ext2", "Linux", "Linux filesystem data"),
+    ("ext3", "Linux", "Linux filesystem data"),
+    ("ext4", "Linux", "Linux filesystem data"),
+    ("xfs", "Linux", "Linux filesystem data"),
+    ("btrfs", "Linux", "Linux filesystem data"),
+    ("swap", "Linux", "Swap partition"),
+    ("home", "Linux", "/home partition"),
+    ("lvm", "Linux", "Logical Volume Manager (LVM) partition"),
+    ("raid", "Linux", "RAID partition"),
+];
+
+/// Find a well-known partition type by a common name of filesystem or partition usage (case
+/// insensitive): `efi`/`esp`, `bios`, `fat`/`fat12`/`fat16`/`fat32`/`vfat`/`exfat`/`ntfs`,
+/// `linux`/`ext2`/`ext3`/`ext4`/`xfs`/`btrfs`, `swap`, `home`, `lvm` and `raid`.
+///
+/// These names are conventions, not part of the specification: the partition type doesn't
+/// describe the filesystem of the partition. For example FAT and NTFS partitions both use the
+/// Windows "Basic data partition" type.
+///
+/// # Examples
+///
+/// Basic usage:
+/// ```
+/// use gptman::partition_types::find_by_alias;
+///
+/// assert_eq!(find_by_alias("ext4").unwrap().name, "Linux filesystem data");
+/// assert_eq!(find_by_alias("FAT32"), find_by_alias("ntfs"));
+/// assert!(find_by_alias("foo").is_none());
+/// ```
+pub fn find_by_alias(alias: &str) -> Option<&'static PartitionType> {
+    let (_, category, name) = ALIASES
+        .iter()
+        .find(|(x, _, _)| x.eq_ignore_ascii_case(alias))?;
+
+    PARTITION_TYPES
+        .iter()
+        .find(|x| &x.category == category && &x.name == name)
+}
+
+/// Attribute bits commonly set on the partitions of a type, by category and name of partition
+/// type.
+const RECOMMENDED_ATTRIBUTES: &[(&str, &str, u64)] = &[
+    // NOTE: legacy BIOS bootable (bit 2)
+    ("None", "EFI System partition", 1 << 2),
+    // NOTE: required partition (bit 0) and no drive letter (bit 63), as documented by Microsoft
+    ("Windows", "Windows Recovery Environment", 1 << 63 | 1),
+];
+
+/// Get the attribute bits commonly set on a partition of the given partition type GUID, or `0` if
+/// the partition type doesn't have any (e.g. Microsoft Reserved Partition) or is unknown.
+///
+/// These are conventions, not part of the specification: this only suggests a default value when
+/// creating a partition.
+///
+/// # Examples
+///
+/// Basic usage:
+/// ```
+/// use gptman::partition_types::{find_by_alias, find_by_name, recommended_attributes};
+///
+/// assert_eq!(recommended_attributes(&find_by_alias("esp").unwrap().guid), 1 << 2);
+/// assert_eq!(
+///     recommended_attributes(&find_by_name("Microsoft Reserved Partition").unwrap().guid),
+///     0
+/// );
+/// assert_eq!(recommended_attributes(&[0xff; 16]), 0);
+/// ```
+pub fn recommended_attributes(guid: &[u8; 16]) -> u64 {
+    find_by_guid(guid)
+        .and_then(|t| {
+            RECOMMENDED_ATTRIBUTES
+                .iter()
+                .find(|(category, name, _)| category == &t.category && name == &t.name)
+        })
+        .map(|(_, _, attributes)| *attributes)
+        .unwrap_or(0)
+}
+
+/// Get the well-known partition types of a category (see [`CATEGORIES`]). This can be used to
+/// build a partition type picker.
+///
+/// # Examples
+///
+/// Basic usage:
+/// ```
+/// use gptman::partition_types::{types_in_category, CATEGORIES};
+///
+/// for category in CATEGORIES {
+///     println!("{}:", category);
+///     for t in types_in_category(category) {
+///         println!("    {}", t.name);
+///     }
+/// }
+///
+/// assert_eq!(types_in_category("Plan 9")[0].name, "Plan 9 partition");
+/// assert!(types_in_category("Foo").is_empty());
+/// ```
+pub fn types_in_category(category: &str) -> Vec<&'static PartitionType> {
+    PARTITION_TYPES
+        .iter()
+        .filter(|x| x.category == category)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn parse_guid() {
+        assert_eq!(
+            guid("C12A7328-F81F-11D2-BA4B-00A0C93EC93B"),
+            [
+                0x28, 0x73, 0x2a, 0xc1, 0x1f, 0xf8, 0xd2, 0x11, 0xba, 0x4b, 0x00, 0xa0, 0xc9, 0x3e,
+                0xc9, 0x3b
+            ]
+        );
+    }
+
+    #[test]
+    fn partition_types_are_unique() {
+        let guids = PARTITION_TYPES
+            .iter()
+            .map(|x| x.guid)
+            .collect::<HashSet<_>>();
+        assert_eq!(guids.len(), PARTITION_TYPES.len());
+
+        let names = PARTITION_TYPES
+            .iter()
+            .map(|x| (x.category, x.name))
+            .collect::<HashSet<_>>();
+        assert_eq!(names.len(), PARTITION_TYPES.len());
+    }
+
+    #[test]
+    fn categories_cover_partition_types() {
+        assert_eq!(
+            CATEGORIES.iter().collect::<HashSet<_>>().len(),
+            CATEGORIES.len()
+        );
+        assert_eq!(
+            CATEGORIES
+                .iter()
+                .map(|x| types_in_category(x).len())
+                .sum::<usize>(),
+            PARTITION_TYPES.len()
+        );
+        assert!(CATEGORIES.iter().all(|x| !types_in_category(x).is_empty()));
+    }
+
+    #[test]
+    fn aliases_are_known_partition_types() {
+        for (alias, _, _) in ALIASES {
+            assert!(find_by_alias(alias).is_some(), "unknown alias: {}", alias);
+        }
+        assert_eq!(
+            find_by_alias("swap").unwrap().guid,
+            guid("0657FD6D-A4AB-43C4-84E5-0933C84B4F4F")
+        );
+    }
+
+    #[test]
+    fn recommended_attributes_are_for_known_partition_types() {
+        for (category, name, attributes) in RECOMMENDED_ATTRIBUTES {
+            let t = types_in_category(category)
+                .into_iter()
+                .find(|x| &x.name == name)
+                .unwrap_or_else(|| panic!("unknown partition type: {}", name));
+            assert_eq!(recommended_attributes(&t.guid), *attributes);
+        }
+        assert_eq!(
+            recommended_attributes(&guid("DE94BBA4-06D1-4D40-A16A-BFD50179D6AC")),
+            0x8000_0000_0000_0001
+        );
+    }
+
+    #[test]
+    fn find_partition_type_by_name() {
+        assert_eq!(
+            find_by_name("LINUX FILESYSTEM DATA").unwrap().guid,
+            guid("0FC63DAF-8483-4772-8E79-3D69D8477DE4")
+        );
+        assert!(matches!(
+            find_by_name("Data partition"),
+            Err(Error::AmbiguousPartitionType(_))
+        ));
+        assert!(matches!(
+            find_by_name("Foo"),
+            Err(Error::UnknownPartitionType(_))
+        ));
+    }
+}
@@ -0,0 +1,142 @@
+use crate::{Error, GPTPartitionEntry, Result, GPT};
+use std::io;
+
+/// The disk GUID of the disks made by [`make_disk`].
+pub const DISK_GUID: [u8; 16] = [0x01; 16];
+
+/// The description of a partition to create with [`make_disk`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartitionSpec<'a> {
+    /// 16 bytes representing the UUID of the partition's type.
+    pub partition_type_guid: [u8; 16],
+    /// 16 bytes representing the UUID of the partition.
+    pub unique_partition_guid: [u8; 16],
+    /// The position (in sectors) of the first sector of the partition.
+    pub starting_lba: u64,
+    /// The size (in sectors) of the partition.
+    pub size: u64,
+    /// The partition name.
+    pub partition_name: &'a str,
+}
+
+/// Make a zero-filled disk of `sectors` sectors of `sector_size` bytes containing a protective
+/// MBR and a valid GPT (primary and backup) with the partitions given in argument.
+///
+/// The partitions are numbered in the order they are given (starting at 1) and the disk GUID is
+/// always [`DISK_GUID`] so the result is deterministic.
+///
+/// # Errors
+///
+/// This function will return an error if the disk is too small to contain a GPT or if the
+/// partitions are not valid (see [`GPT::verify`]).
+///
+/// # Examples
+///
+/// Basic usage:
+/// ```
+/// use gptman::testing::{make_disk, PartitionSpec};
+///
+/// let data = make_disk(512, 100, &[PartitionSpec {
+///     partition_type_guid: [0xff; 16],
+///     unique_partition_guid: [0xff; 16],
+///     starting_lba: 34,
+///     size: 10,
+///     partition_name: "A Robot Named Fight!",
+/// }])
+/// .expect("could not make disk");
+///
+/// let gpt = gptman::GPT::read_from(&mut std::io::Cursor::new(data), 512)
+///     .expect("could not read the partition table");
+/// assert_eq!(gpt[1].ending_lba, 43);
+/// ```
+pub fn make_disk(sector_size: u64, sectors: u64, partitions: &[PartitionSpec]) -> Result<Vec<u8>> {
+    let len = sector_size.checked_mul(sectors).ok_or(Error::Overflow)?;
+    let mut cur = io::Cursor::new(vec![0; len as usize]);
+    let mut gpt = GPT::new_from(&mut cur, sector_size, DISK_GUID)?;
+
+    for (i, spec) in partitions.iter().enumerate() {
+        gpt.check_partition_number(i as u32 + 1)?;
+        gpt[i as u32 + 1] = GPTPartitionEntry {
+            partition_type_guid: spec.partition_type_guid,
+            unique_partition_guid: spec.unique_partition_guid,
+            starting_lba: spec.starting_lba,
+            ending_lba: spec
+                .starting_lba
+                .checked_add(spec.size)
+                .ok_or(Error::Overflow)?
+                .saturating_sub(1),
+            attribute_bits: 0,
+            partition_name: spec.partition_name.into(),
+        };
+    }
+
+    GPT::write_protective_mbr_into(&mut cur, sector_size)?;
+    gpt.write_into(&mut cur)?;
+
+    Ok(cur.into_inner())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn make_disk_round_trip() {
+        fn test(ss: u64) {
+            let specs = [
+                PartitionSpec {
+                    partition_type_guid: [1; 16],
+                    unique_partition_guid: [1; 16],
+                    starting_lba: 40,
+                    size: 8,
+                    partition_name: "Foo",
+                },
+                PartitionSpec {
+                    partition_type_guid: [2; 16],
+                    unique_partition_guid: [2; 16],
+                    starting_lba: 48,
+                    size: 16,
+                    partition_name: "Bar",
+                },
+            ];
+            let data = make_disk(ss, 100, &specs).unwrap();
+            assert_eq!(data.len() as u64, ss * 100);
+            assert_eq!(data[510..512], [0x55, 0xaa]);
+
+            let gpt = GPT::read_from(&mut io::Cursor::new(data), ss).unwrap();
+            assert_eq!(gpt.header.disk_guid, DISK_GUID);
+            assert_eq!(gpt.align, 8);
+            for (i, spec) in specs.iter().enumerate() {
+                let p = &gpt[i as u32 + 1];
+                assert_eq!(p.starting_lba, spec.starting_lba);
+                assert_eq!(p.size().unwrap(), spec.size);
+                assert_eq!(p.partition_name.as_str(), spec.partition_name);
+            }
+            assert!(gpt[3].is_unused());
+        }
+
+        test(512);
+        test(4096);
+    }
+
+    #[test]
+    fn make_disk_with_invalid_partitions() {
+        let spec = PartitionSpec {
+            partition_type_guid: [1; 16],
+            unique_partition_guid: [1; 16],
+            starting_lba: 40,
+            size: 100,
+            partition_name: "Foo",
+        };
+        assert!(make_disk(
+            512,
+            100,
+            &[PartitionSpec {
+                size: 0,
+                ..spec.clone()
+            }]
+        )
+        .is_err());
+        assert!(make_disk(512, 100, &[spec]).is_err());
+    }
+}